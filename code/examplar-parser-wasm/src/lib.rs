@@ -0,0 +1 @@
+pub use parser::wasm::parse_with_grammar;
@@ -0,0 +1,69 @@
+use std::env;
+use std::fmt::Debug;
+use std::fs::read_to_string;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use parser::framework::{Parser, ParseResult};
+use parser::framework::grammar::Grammar;
+
+const USAGE: &str = "usage: examplar lsystem
+       examplar grammar <file> [rule]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["lsystem"] => repl(|input| report(parser::system().parse(input))),
+
+        ["grammar", file, rule @ ..] if rule.len() <= 1 => {
+            let definition = read_to_string(file).unwrap_or_else(|e| fail(&format!("cannot read {}: {}", file, e)));
+            let grammar = Grammar::new(&definition).unwrap_or_else(|e| fail(&format!("invalid grammar: {}", e)));
+            let rule = rule.first().copied().unwrap_or_else(|| grammar.start());
+            let parser = grammar.rule(rule).unwrap_or_else(|e| fail(&e.to_string()));
+            repl(|input| report(parser.parse(input)))
+        }
+
+        _ => fail(USAGE),
+    }
+}
+
+// Inputs may span several lines; an empty line ends one and parses it.
+fn repl<F>(run: F) where F: Fn(&str) {
+    let stdin = io::stdin();
+    let mut input = String::new();
+    prompt(&input);
+    for line in stdin.lock().lines() {
+        let line = line.unwrap_or_else(|e| fail(&e.to_string()));
+        if line.is_empty() && !input.is_empty() {
+            run(&input);
+            input.clear();
+        } else {
+            input.push_str(&line);
+            input.push('\n');
+        }
+        prompt(&input);
+    }
+}
+
+fn prompt(input: &str) {
+    print!("{}", if input.is_empty() { "> " } else { ". " });
+    io::stdout().flush().unwrap_or_else(|e| fail(&e.to_string()));
+}
+
+fn report<T: Debug>(result: ParseResult<'_, T>) {
+    match result {
+        Ok((value, rem)) => {
+            println!("{:#?}", value);
+            if !rem.is_empty() {
+                println!("remaining: {:?}", rem);
+            }
+        }
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
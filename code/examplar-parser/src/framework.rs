@@ -1,40 +1,428 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+#[cfg(feature = "bumpalo")]
+pub mod arena;
+pub mod ast;
+pub mod bytes;
+pub mod chunks;
+mod context;
+pub mod coverage;
+pub mod formats;
+pub mod grammar;
+pub mod highlight;
+pub mod intern;
+pub mod print;
+pub mod source;
+pub mod testing;
+pub mod unicode;
+
+pub use self::context::{Budget, Budgeted, Context, DepthLimited, Metrics, Snapshot, Warning, alternative, budgeted, depth_limited, expect, memoized, parse_recovering, parse_warned, recover, speculative, warn_if};
 
 pub fn parse(_input: &str) -> Result<(), ParseError> {
   Ok(())
 }
 
-#[derive(Debug, PartialEq)]
+const SNIPPET_LENGTH: usize = 16;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Found {
+    Character(char),
+    Text(String),
+    EndOfInput,
+}
+
+thread_local! {
+    // The address of the input the latest `Found` was taken from. Errors do not
+    // carry offsets, but they are made where parsing failed, from the input
+    // left there, so this is where the error being returned happened.
+    static FOUND_AT: Cell<usize> = const { Cell::new(0) };
+}
+
+impl Found {
+    pub fn at(input: &str) -> Self {
+        FOUND_AT.with(|at| at.set(input.as_ptr() as usize));
+        match input.chars().next() {
+            Some(c) => Found::Character(c),
+            None => Found::EndOfInput,
+        }
+    }
+
+    pub fn snippet(input: &str, length: usize) -> Self {
+        FOUND_AT.with(|at| at.set(input.as_ptr() as usize));
+        if input.is_empty() {
+            return Found::EndOfInput
+        }
+        Found::Text(input.chars().take(length.max(1)).collect())
+    }
+}
+
+impl Display for Found {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Found::Character(c) => write!(f, "{:?}", c),
+            Found::Text(text) => write!(f, "{:?}", text),
+            Found::EndOfInput => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum ParseError {
     GenericError,
-    ExpectingCharacter(char),
-    ExpectingPredicate,
-    ExpectingOneOfToParse,
-    ExpectingLiteral(String),
+    ExpectingCharacter(char, Found),
+    ExpectingPredicate(Found),
+    ExpectingNamed(String, Found),
+    ExpectingOneOfToParse(Found),
+    ExpectingLiteral(String, Found),
+    ExpectingPattern(String, Found),
     EndOfInput,
-    ExpectingToBeAtEndOfInput
+    ExpectingToBeAtEndOfInput(Found),
+    UnexpectedFollowingInput(Found),
+    ExpectingWordBoundary(Found),
+    RecursionLimitExceeded,
+    BudgetExceeded,
+    InfiniteLoopDetected,
+    UnknownRule(String),
+    LookaheadExceeded(usize),
+    Overflow(Found),
+    InContext(Box<ParseError>, String),
+    Committed(Box<ParseError>),
+    Misspelled(String, Found),
+    ChecksumMismatch(usize),
+    LeftRecursion(String),
+    DuplicateRule(String),
+    InvalidEdit(usize, usize),
+    NotInSource,
+    Incomplete,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::GenericError => write!(f, "could not parse input"),
+            ParseError::ExpectingCharacter(c, found) => write!(f, "expected {:?}, found {}", c, found),
+            ParseError::ExpectingPredicate(found) => write!(f, "expected a matching character, found {}", found),
+            ParseError::ExpectingNamed(name, found) => write!(f, "expected {}, found {}", name, found),
+            ParseError::ExpectingOneOfToParse(found) => write!(f, "expected one of the alternatives, found {}", found),
+            ParseError::ExpectingLiteral(literal, found) => write!(f, "expected {:?}, found {}", literal, found),
+            ParseError::ExpectingPattern(pattern, found) => write!(f, "expected input matching {:?}, found {}", pattern, found),
+            ParseError::EndOfInput => write!(f, "unexpected end of input"),
+            ParseError::ExpectingToBeAtEndOfInput(found) => write!(f, "expected end of input, found {}", found),
+            ParseError::UnexpectedFollowingInput(found) => write!(f, "unexpected {}", found),
+            ParseError::ExpectingWordBoundary(found) => write!(f, "expected a word boundary, found {}", found),
+            ParseError::RecursionLimitExceeded => write!(f, "recursion limit exceeded"),
+            ParseError::BudgetExceeded => write!(f, "parse budget exceeded"),
+            ParseError::InfiniteLoopDetected => write!(f, "repetition did not consume any input"),
+            ParseError::UnknownRule(name) => write!(f, "unknown rule {:?}", name),
+            ParseError::LookaheadExceeded(limit) => write!(f, "could not decide within {} characters", limit),
+            ParseError::Overflow(found) => write!(f, "{} is out of range", found),
+            ParseError::InContext(error, context) => write!(f, "{} in {:?}", error, context),
+            ParseError::Committed(error) => write!(f, "{}", error),
+            ParseError::Misspelled(suggestion, found) => write!(f, "unexpected {}, did you mean {:?}?", found, suggestion),
+            ParseError::ChecksumMismatch(length) => write!(f, "integrity check failed over {} bytes", length),
+            ParseError::LeftRecursion(name) => write!(f, "rule {:?} is left-recursive", name),
+            ParseError::DuplicateRule(name) => write!(f, "rule {:?} is defined more than once", name),
+            ParseError::InvalidEdit(start, end) => write!(f, "edit {}..{} is not a range of the text", start, end),
+            ParseError::NotInSource => write!(f, "input is not part of the source spans are measured in"),
+            ParseError::Incomplete => write!(f, "input ended part way through"),
+        }
+    }
+}
+
+impl ParseError {
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            ParseError::InContext(error, _) => error.is_fatal(),
+            ParseError::Committed(_) => true,
+            _ => matches!(self, ParseError::RecursionLimitExceeded | ParseError::BudgetExceeded | ParseError::InfiniteLoopDetected | ParseError::NotInSource | ParseError::Incomplete),
+        }
+    }
+
+    // Codes are stable across releases: new variants get new codes and codes of
+    // removed variants are not reused.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::GenericError => "E0001",
+            ParseError::ExpectingCharacter(..) => "E0002",
+            ParseError::ExpectingPredicate(..) => "E0003",
+            ParseError::ExpectingNamed(..) => "E0004",
+            ParseError::ExpectingOneOfToParse(..) => "E0005",
+            ParseError::ExpectingLiteral(..) => "E0006",
+            ParseError::ExpectingPattern(..) => "E0007",
+            ParseError::EndOfInput => "E0008",
+            ParseError::ExpectingToBeAtEndOfInput(..) => "E0009",
+            ParseError::UnexpectedFollowingInput(..) => "E0010",
+            ParseError::ExpectingWordBoundary(..) => "E0011",
+            ParseError::RecursionLimitExceeded => "E0012",
+            ParseError::BudgetExceeded => "E0013",
+            ParseError::InfiniteLoopDetected => "E0014",
+            ParseError::UnknownRule(..) => "E0015",
+            ParseError::LookaheadExceeded(..) => "E0016",
+            ParseError::Overflow(..) => "E0017",
+            ParseError::InContext(error, _) | ParseError::Committed(error) => error.code(),
+            ParseError::Misspelled(..) => "E0018",
+            ParseError::ChecksumMismatch(..) => "E0019",
+            ParseError::LeftRecursion(..) => "E0020",
+            ParseError::DuplicateRule(..) => "E0021",
+            ParseError::InvalidEdit(..) => "E0022",
+            ParseError::NotInSource => "E0023",
+            ParseError::Incomplete => "E0024",
+        }
+    }
+
+    pub fn expected(&self) -> Option<String> {
+        match self {
+            ParseError::ExpectingCharacter(c, _) => Some(format!("{:?}", c)),
+            ParseError::ExpectingPredicate(_) => Some("a matching character".to_owned()),
+            ParseError::ExpectingNamed(name, _) => Some(name.clone()),
+            ParseError::ExpectingOneOfToParse(_) => Some("one of the alternatives".to_owned()),
+            ParseError::ExpectingLiteral(literal, _) => Some(format!("{:?}", literal)),
+            ParseError::ExpectingPattern(pattern, _) => Some(format!("input matching {:?}", pattern)),
+            ParseError::ExpectingToBeAtEndOfInput(_) => Some("end of input".to_owned()),
+            ParseError::ExpectingWordBoundary(_) => Some("a word boundary".to_owned()),
+            ParseError::InContext(error, _) | ParseError::Committed(error) => error.expected(),
+            ParseError::Misspelled(suggestion, _) => Some(format!("{:?}", suggestion)),
+            _ => None,
+        }
+    }
+
+    // The same error having found something else, for when it was reported
+    // against part of the input and has to be told in terms of the whole.
+    fn with_found(self, found: Found) -> ParseError {
+        match self {
+            ParseError::ExpectingCharacter(c, _) => ParseError::ExpectingCharacter(c, found),
+            ParseError::ExpectingPredicate(_) => ParseError::ExpectingPredicate(found),
+            ParseError::ExpectingNamed(name, _) => ParseError::ExpectingNamed(name, found),
+            ParseError::ExpectingOneOfToParse(_) => ParseError::ExpectingOneOfToParse(found),
+            ParseError::ExpectingLiteral(literal, _) => ParseError::ExpectingLiteral(literal, found),
+            ParseError::ExpectingPattern(pattern, _) => ParseError::ExpectingPattern(pattern, found),
+            ParseError::ExpectingToBeAtEndOfInput(_) => ParseError::ExpectingToBeAtEndOfInput(found),
+            ParseError::UnexpectedFollowingInput(_) => ParseError::UnexpectedFollowingInput(found),
+            ParseError::ExpectingWordBoundary(_) => ParseError::ExpectingWordBoundary(found),
+            ParseError::Overflow(_) => ParseError::Overflow(found),
+            ParseError::Misspelled(suggestion, _) => ParseError::Misspelled(suggestion, found),
+            ParseError::InContext(error, context) => ParseError::InContext(Box::new(error.with_found(found)), context),
+            ParseError::Committed(error) => ParseError::Committed(Box::new(error.with_found(found))),
+            error => error,
+        }
+    }
+
+    pub fn found(&self) -> Option<&Found> {
+        match self {
+            ParseError::ExpectingCharacter(_, found)
+            | ParseError::ExpectingPredicate(found)
+            | ParseError::ExpectingNamed(_, found)
+            | ParseError::ExpectingOneOfToParse(found)
+            | ParseError::ExpectingLiteral(_, found)
+            | ParseError::ExpectingPattern(_, found)
+            | ParseError::ExpectingToBeAtEndOfInput(found)
+            | ParseError::UnexpectedFollowingInput(found)
+            | ParseError::ExpectingWordBoundary(found)
+            | ParseError::Overflow(found)
+            | ParseError::Misspelled(_, found) => Some(found),
+            ParseError::InContext(error, _) | ParseError::Committed(error) => error.found(),
+            _ => None,
+        }
+    }
+}
+
+// Overrides how errors are worded without changing the errors themselves,
+// e.g. to translate messages or to name domain concepts. Only the parts that
+// need different wording have to be implemented.
+pub trait Phrasing {
+    fn expected(&self, error: &ParseError) -> Option<String> {
+        error.expected()
+    }
+
+    fn found(&self, found: &Found) -> String {
+        found.to_string()
+    }
+
+    fn message(&self, error: &ParseError) -> String {
+        match (self.expected(error), error.found()) {
+            (Some(expected), Some(found)) => format!("expected {}, found {}", expected, self.found(found)),
+            _ => error.to_string(),
+        }
+    }
+}
+
+pub struct DefaultPhrasing;
+
+impl Phrasing for DefaultPhrasing {}
+
+impl ParseError {
+    pub fn render_with<P>(&self, phrasing: &P) -> String where P: Phrasing {
+        phrasing.message(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ParseError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        use serde::ser::SerializeStruct;
+
+        let mut error = serializer.serialize_struct("ParseError", 4)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("expected", &self.expected())?;
+        error.serialize_field("found", &self.found().map(Found::to_string))?;
+        error.end()
+    }
 }
 
+pub type ParseResult<'a, T, E = ParseError> = Result<(T, &'a str), E>;
+
 pub trait Parser<'a, T> {
-    fn parse(&self, input: &'a str) -> Result<(T, &'a str), ParseError>;
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T>;
+
+    fn parse_measured(&self, input: &'a str) -> Result<Parsed<'a, T>, ParseError> {
+        let (value, rest) = self.parse(input)?;
+        Ok(Parsed { value, rest, consumed: input.len() - rest.len() })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parsed<'a, T> {
+    pub value: T,
+    pub rest: &'a str,
+    pub consumed: usize,
+}
+
+impl<'a, T> Parsed<'a, T> {
+    // Where parsing stopped in `source`, which the parsed input is a suffix of.
+    pub fn end(&self, source: &str) -> usize {
+        source.len() - self.rest.len()
+    }
+}
+
+impl <'a, T, F> Parser<'a, T> for F where F: Fn(&'a str) -> ParseResult<'a, T> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+// For parsers that can only run once, like closures that move a captured
+// value into their result. Every `Fn` closure is also `FnOnce`, so ordinary
+// closures work here too; other parsers go through `once`.
+pub trait ParserOnce<'a, T> {
+    fn parse_once(self, input: &'a str) -> ParseResult<'a, T>;
 }
 
-impl <'a, T, F> Parser<'a, T> for F where F: Fn(&'a str) -> Result<(T, &'a str), ParseError> {
-    fn parse(&self, input: &'a str) -> Result<(T, &'a str), ParseError> {
+impl<'a, T, F> ParserOnce<'a, T> for F where F: FnOnce(&'a str) -> ParseResult<'a, T> {
+    fn parse_once(self, input: &'a str) -> ParseResult<'a, T> {
         self(input)
     }
 }
 
+pub struct Once<P>(P);
+
+impl<'a, T, P> ParserOnce<'a, T> for Once<P> where P: Parser<'a, T> {
+    fn parse_once(self, input: &'a str) -> ParseResult<'a, T> {
+        self.0.parse(input)
+    }
+}
+
+pub fn once<P>(parser: P) -> Once<P> {
+    Once(parser)
+}
+
+// Lets a one-shot parser be passed where a `Parser` is wanted, such as to a
+// combinator that is known to call it only once. Running it a second time is a
+// bug in the caller and panics.
+pub struct AtMostOnce<P>(Cell<Option<P>>);
+
+impl<'a, T, P> Parser<'a, T> for AtMostOnce<P> where P: ParserOnce<'a, T> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        let parser = self.0.take().expect("a one-shot parser to be run at most once");
+        parser.parse_once(input)
+    }
+}
+
+pub fn at_most_once<P>(parser: P) -> AtMostOnce<P> {
+    AtMostOnce(Cell::new(Some(parser)))
+}
+
+// `Box<dyn Parser>` cannot implement `Parser` without overlapping the impl
+// for closures above, so trait objects are wrapped instead. This lets
+// parsers of different types be collected and chosen between at runtime.
+pub struct BoxedParser<'p, 'a, T>(Box<dyn Parser<'a, T> + 'p>);
+
+impl<'p, 'a, T> Parser<'a, T> for BoxedParser<'p, 'a, T> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        self.0.parse(input)
+    }
+}
+
+impl<'p, 'a, T> BoxedParser<'p, 'a, T> {
+    pub fn new<P>(parser: P) -> Self where P: Parser<'a, T> + 'p {
+        Self(Box::new(parser))
+    }
+}
+
+impl<'p, 'a, T> From<Box<dyn Parser<'a, T> + 'p>> for BoxedParser<'p, 'a, T> {
+    fn from(parser: Box<dyn Parser<'a, T> + 'p>) -> Self {
+        Self(parser)
+    }
+}
+
+pub fn boxed<'p, 'a, T, P>(parser: P) -> BoxedParser<'p, 'a, T> where P: Parser<'a, T> + 'p {
+    BoxedParser::new(parser)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Checkpoint(usize);
+
+pub struct Cursor<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.offset)
+    }
+
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.offset = checkpoint.0;
+    }
+
+    pub fn parse<T>(&mut self, parser: &impl Parser<'a, T>) -> Result<T, ParseError> {
+        let (result, rem) = parser.parse(self.rest())?;
+        self.offset = self.input.len() - rem.len();
+        Ok(result)
+    }
+}
+
+
 pub struct Character {
   character_to_match: char,
 }
 
 impl<'a> Parser<'a, char> for Character {
-    fn parse(&self, input: &'a str) -> Result<(char, &'a str), ParseError> {
-        if input.starts_with(self.character_to_match) {
-            Ok((self.character_to_match, &input[1..]))
+    fn parse(&self, input: &'a str) -> ParseResult<'a, char> {
+        let c = self.character_to_match;
+        if c.is_ascii() && input.as_bytes().first() == Some(&(c as u8)) {
+            Ok((c, &input[1..]))
+        } else if !c.is_ascii() && input.starts_with(c) {
+            Ok((c, &input[c.len_utf8()..]))
         } else {
-            Err(ParseError::ExpectingCharacter(self.character_to_match))
+            Err(ParseError::ExpectingCharacter(self.character_to_match, Found::at(input)))
         }
     }
 }
@@ -53,15 +441,24 @@ pub struct Any<F> where F: Fn(char) -> bool + Sized {
     predicate: F,
 }
 
+// ASCII is by far the most common input, so it is read straight from the bytes
+// and only other characters pay for UTF-8 decoding.
+fn next_char(input: &str) -> Option<char> {
+    match input.as_bytes().first() {
+        Some(byte) if byte.is_ascii() => Some(*byte as char),
+        _ => input.chars().next(),
+    }
+}
+
 impl<'a, F> Parser<'a, char> for Any<F> where F: Fn(char) -> bool + Sized {
-    fn parse(&self, input: &'a str) -> Result<(char, &'a str), ParseError> {
-        let character = input.chars().next();
+    fn parse(&self, input: &'a str) -> ParseResult<'a, char> {
+        let character = next_char(input);
         match character {
             Some(c) => {
                 if (self.predicate)(c) {
-                    Ok((c, &input[1..]))
+                    Ok((c, &input[c.len_utf8()..]))
                 } else {
-                    Err(ParseError::ExpectingPredicate)
+                    Err(ParseError::ExpectingPredicate(Found::Character(c)))
                 }
             },
 
@@ -82,17 +479,42 @@ pub fn any<'a, F>(predicate: F) -> impl Parser<'a, char> where F: Fn(char) -> bo
     Any::new(predicate)
 }
 
+pub struct Satisfy<F> where F: Fn(char) -> bool + Sized {
+    name: String,
+    predicate: F,
+}
+
+impl<'a, F> Parser<'a, char> for Satisfy<F> where F: Fn(char) -> bool + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, char> {
+        match next_char(input) {
+            Some(c) if (self.predicate)(c) => Ok((c, &input[c.len_utf8()..])),
+
+            _ => Err(ParseError::ExpectingNamed(self.name.clone(), Found::at(input))),
+        }
+    }
+}
+
+impl<F> Satisfy<F> where F: Fn(char) -> bool + Sized {
+    pub fn new<S>(name: S, predicate: F) -> Self where S: Into<String> {
+        Self { name: name.into(), predicate }
+    }
+}
+
+pub fn satisfy_named<'a, S, F>(name: S, predicate: F) -> impl Parser<'a, char> where S: Into<String>, F: Fn(char) -> bool + Sized {
+    Satisfy::new(name, predicate)
+}
+
 pub struct Literal<'p>(&'p str);
 
 impl <'a, 'p> Parser<'a, &'a str> for Literal<'p> {
-    fn parse(&self, input: &'a str) -> Result<(&'a str, &'a str), ParseError> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
         if input.starts_with(self.0) {
             let len = self.0.len();
             let substr = &input[..len];
             let rem = &input[len..];
             Ok((substr, rem))
         } else {
-            Err(ParseError::ExpectingLiteral(self.0.to_owned()))
+            Err(ParseError::ExpectingLiteral(self.0.to_owned(), Found::snippet(input, self.0.chars().count())))
         }
     }
 }
@@ -102,6 +524,226 @@ pub fn literal(match_exactly: &str) -> Literal {
 }
 
 
+pub struct TakeWhile<F> where F: Fn(char) -> bool + Sized {
+    predicate: F,
+}
+
+impl<'a, F> Parser<'a, &'a str> for TakeWhile<F> where F: Fn(char) -> bool + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        let mut index = 0;
+        while let Some(c) = next_char(&input[index..]) {
+            if !(self.predicate)(c) {
+                break;
+            }
+            index += c.len_utf8();
+        }
+        Ok((&input[..index], &input[index..]))
+    }
+}
+
+impl<F> TakeWhile<F> where F: Fn(char) -> bool + Sized {
+    pub fn new(predicate: F) -> Self {
+        Self { predicate }
+    }
+}
+
+pub fn take_while<'a, F>(predicate: F) -> impl Parser<'a, &'a str> where F: Fn(char) -> bool + Sized {
+    TakeWhile::new(predicate)
+}
+
+pub fn take_till<'a, F>(predicate: F) -> impl Parser<'a, &'a str> where F: Fn(char) -> bool + Sized {
+    TakeWhile::new(move |c| !predicate(c))
+}
+
+pub struct TakeUntil<'p>(&'p str);
+
+impl <'a, 'p> Parser<'a, &'a str> for TakeUntil<'p> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        match input.find(self.0) {
+            Some(index) => Ok((&input[..index], &input[index..])),
+            None => Err(ParseError::ExpectingLiteral(self.0.to_owned(), Found::EndOfInput)),
+        }
+    }
+}
+
+pub fn take_until(delimiter: &str) -> TakeUntil<'_> {
+    TakeUntil(delimiter)
+}
+
+pub fn take_until_and_consume<'a, 'p>(delimiter: &'p str) -> impl Parser<'a, (&'a str, &'a str)> + 'p {
+    move |input: &'a str| {
+        let (content, rem) = take_until(delimiter).parse(input)?;
+        let (delimiter, rem) = literal(delimiter).parse(rem)?;
+        Ok(((content, delimiter), rem))
+    }
+}
+
+pub struct SkipUntil<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    parser: P,
+    phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T, P> SkipUntil<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    pub fn new(parser: P) -> Self {
+        Self { parser, phantom: PhantomData }
+    }
+
+    fn scan(&self, input: &'a str) -> Result<(&'a str, T, &'a str), ParseError> {
+        let offsets = input.char_indices().map(|(offset, _)| offset).chain(Some(input.len()));
+        let mut failure = ParseError::EndOfInput;
+        for offset in offsets {
+            match self.parser.parse(&input[offset..]) {
+                Ok((result, rem)) => return Ok((&input[..offset], result, rem)),
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(e) => failure = e,
+            }
+        }
+        Err(failure)
+    }
+}
+
+impl<'a, T, P> Parser<'a, &'a str> for SkipUntil<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        let (skipped, _, _) = self.scan(input)?;
+        Ok((skipped, &input[skipped.len()..]))
+    }
+}
+
+// Returns what was skipped, so its length is how far the scan had to go.
+pub fn skip_until<'a, T, P>(parser: P) -> impl Parser<'a, &'a str> where T: 'a, P: Parser<'a, T> + Sized {
+    SkipUntil::new(parser)
+}
+
+pub fn skip_until_and_consume<'a, T, P>(parser: P) -> impl Parser<'a, (&'a str, T)> where T: 'a, P: Parser<'a, T> + Sized {
+    let skip = SkipUntil::new(parser);
+    move |input| {
+        let (skipped, result, rem) = skip.scan(input)?;
+        Ok(((skipped, result), rem))
+    }
+}
+
+#[cfg(feature = "regex")]
+pub struct Regex {
+    pattern: String,
+    regex: ::regex::Regex,
+}
+
+#[cfg(feature = "regex")]
+impl<'a> Parser<'a, &'a str> for Regex {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        match self.regex.find(input) {
+            Some(found) => Ok((found.as_str(), &input[found.end()..])),
+            None => Err(ParseError::ExpectingPattern(self.pattern.clone(), Found::snippet(input, SNIPPET_LENGTH))),
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, ::regex::Error> {
+        let regex = ::regex::Regex::new(&format!("^(?:{})", pattern))?;
+        Ok(Self { pattern: pattern.to_owned(), regex })
+    }
+}
+
+#[cfg(feature = "regex")]
+pub fn regex<'a>(pattern: &str) -> impl Parser<'a, &'a str> {
+    Regex::new(pattern).expect("invalid regular expression")
+}
+
+#[cfg(feature = "regex")]
+pub struct RegexCaptures(Regex);
+
+#[cfg(feature = "regex")]
+impl<'a> Parser<'a, Vec<Option<&'a str>>> for RegexCaptures {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Vec<Option<&'a str>>> {
+        match self.0.regex.captures(input) {
+            Some(captures) => {
+                let end = captures.get(0).map(|found| found.end()).unwrap_or(0);
+                let groups = captures.iter().map(|group| group.map(|found| found.as_str())).collect();
+                Ok((groups, &input[end..]))
+            }
+            None => Err(ParseError::ExpectingPattern(self.0.pattern.clone(), Found::snippet(input, SNIPPET_LENGTH))),
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+pub fn regex_captures<'a>(pattern: &str) -> impl Parser<'a, Vec<Option<&'a str>>> {
+    RegexCaptures(Regex::new(pattern).expect("invalid regular expression"))
+}
+
+
+pub struct Glob {
+    pattern: String,
+}
+
+impl<'a> Parser<'a, (&'a str, Vec<&'a str>)> for Glob {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, (&'a str, Vec<&'a str>)> {
+        let mut captures = vec![];
+        match match_glob(&self.pattern, input, 0, &mut captures) {
+            Some(end) => Ok(((&input[..end], captures), &input[end..])),
+            None => Err(ParseError::ExpectingPattern(self.pattern.clone(), Found::snippet(input, SNIPPET_LENGTH))),
+        }
+    }
+}
+
+impl Glob {
+    pub fn new<S>(pattern: S) -> Self where S: Into<String> {
+        Self { pattern: pattern.into() }
+    }
+}
+
+pub fn glob<'a, S>(pattern: S) -> impl Parser<'a, (&'a str, Vec<&'a str>)> where S: Into<String> {
+    Glob::new(pattern)
+}
+
+// Wildcards never match whitespace, so a pattern stops at the end of a word.
+// A `*` prefers the longest run and backtracks when the rest of the pattern fails.
+fn match_glob<'a>(pattern: &str, input: &'a str, position: usize, captures: &mut Vec<&'a str>) -> Option<usize> {
+    let mut characters = pattern.chars();
+    let rest = &input[position..];
+    match characters.next() {
+        None => Some(position),
+
+        Some('*') => {
+            let remaining_pattern = characters.as_str();
+            let mut end = position + rest.find(char::is_whitespace).unwrap_or(rest.len());
+            loop {
+                captures.push(&input[position..end]);
+                if let Some(result) = match_glob(remaining_pattern, input, end, captures) {
+                    return Some(result);
+                }
+                captures.pop();
+                match input[position..end].char_indices().last() {
+                    Some((index, _)) => end = position + index,
+                    None => return None,
+                }
+            }
+        }
+
+        Some('?') => {
+            let character = rest.chars().next().filter(|c| !c.is_whitespace())?;
+            let end = position + character.len_utf8();
+            captures.push(&input[position..end]);
+            let result = match_glob(characters.as_str(), input, end, captures);
+            if result.is_none() {
+                captures.pop();
+            }
+            result
+        }
+
+        Some(character) => {
+            if rest.starts_with(character) {
+                match_glob(characters.as_str(), input, position + character.len_utf8(), captures)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+
 pub struct Map<'a, I, O, P, F> where I: 'a, P: Parser<'a, I> + Sized, F: Fn(I) -> O + Sized {
     parser: P,
     map: F,
@@ -109,7 +751,7 @@ pub struct Map<'a, I, O, P, F> where I: 'a, P: Parser<'a, I> + Sized, F: Fn(I) -
 }
 
 impl<'a, I, O, P, F> Parser<'a, O> for Map<'a, I, O, P, F> where I: 'a, P: Parser<'a, I> + Sized, F: Fn(I) -> O + Sized {
-    fn parse(&self, input: &'a str) -> Result<(O, &'a str), ParseError> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, O> {
         let attempt = self.parser.parse(input);
         attempt.map(|(v, rest)|{ ((self.map)(v), rest)})
     }
@@ -125,34 +767,116 @@ pub fn map<'a, I, O, P, F>(parser: P, map: F) -> impl Parser<'a, O> where I: 'a,
     Map::new(parser, map)
 }
 
-pub struct Between<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
-    lower_limit: u8,
-    upper_limit: Limit,
-    parser: P,
-    phantom: PhantomData<&'a T>,
+pub fn collect_string<'a, I, P>(parser: P) -> impl Parser<'a, String> where I: IntoIterator<Item = char> + 'a, P: Parser<'a, I> + Sized {
+    map(parser, |characters| characters.into_iter().collect())
+}
+
+// The borrowed counterpart of collect_string: the text the parser consumed,
+// without building anything from its result.
+pub fn recognize<'a, T, P>(parser: P) -> impl Parser<'a, &'a str> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        let (_, rem) = parser.parse(input)?;
+        Ok((&input[..input.len() - rem.len()], rem))
+    }
+}
+
+// Exactly `N` items, straight into an array rather than a `Vec`. The first
+// item that fails ends the parse with its error.
+pub fn array<'a, T, P, const N: usize>(parser: P) -> impl Parser<'a, [T; N]> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        let mut rem = input;
+        let mut error = None;
+        let items: [Option<T>; N] = std::array::from_fn(|_| {
+            if error.is_some() {
+                return None;
+            }
+            match parser.parse(rem) {
+                Ok((item, rest)) => {
+                    rem = rest;
+                    Some(item)
+                }
+                Err(e) => {
+                    error = Some(e);
+                    None
+                }
+            }
+        });
+        match error {
+            Some(e) => Err(e),
+            None => Ok((items.map(|item| item.expect("an item for every slot")), rem)),
+        }
+    }
 }
 
-pub enum Limit {
-  At(u8),
-  Infinity,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    Exactly(usize),
+    AtLeast(usize),
+    AtMost(usize),
+    Between(usize, usize),
 }
 
-impl Limit {
-  pub fn is_bigger_then(&self, n: u8) -> bool {
-    match self {
-      Limit::At(threshold) => threshold > &n,
+impl Repeat {
+    pub fn minimum(&self) -> usize {
+        match *self {
+            Repeat::Exactly(n) | Repeat::AtLeast(n) | Repeat::Between(n, _) => n,
+            Repeat::AtMost(_) => 0,
+        }
+    }
+
+    pub fn maximum(&self) -> Option<usize> {
+        match *self {
+            Repeat::Exactly(n) | Repeat::AtMost(n) | Repeat::Between(_, n) => Some(n),
+            Repeat::AtLeast(_) => None,
+        }
+    }
+
+    fn allows_more_than(&self, count: usize) -> bool {
+        !matches!(self.maximum(), Some(maximum) if count >= maximum)
+    }
+}
 
-      Limit::Infinity => true,
+// An empty range allows no count at all, which is a mistake rather than a
+// repetition, so it panics like indexing with one would.
+impl<R> From<R> for Repeat where R: RangeBounds<usize> {
+    fn from(range: R) -> Self {
+        let minimum = match range.start_bound() {
+            Bound::Included(n) => *n,
+            Bound::Excluded(n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let maximum = match range.end_bound() {
+            Bound::Included(n) => {
+                assert!(*n >= minimum, "repetition range {}..={} is empty", minimum, n);
+                Some(*n)
+            }
+            Bound::Excluded(n) => {
+                assert!(*n > minimum, "repetition range {}..{} is empty", minimum, n);
+                Some(n - 1)
+            }
+            Bound::Unbounded => None,
+        };
+        match maximum {
+            None => Repeat::AtLeast(minimum),
+            Some(maximum) if maximum == minimum => Repeat::Exactly(minimum),
+            Some(maximum) if minimum == 0 => Repeat::AtMost(maximum),
+            Some(maximum) => Repeat::Between(minimum, maximum),
+        }
     }
-  }
+}
+
+pub struct Between<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    repeat: Repeat,
+    parser: P,
+    phantom: PhantomData<&'a T>,
 }
 
 impl<'a, T, P> Parser<'a, Vec<T>> for Between<'a, T, P> where P: Parser<'a, T> + Sized {
-    fn parse(&self, input: &'a str) -> Result<(Vec<T>, &'a str), ParseError> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Vec<T>> {
         let mut result = vec![];
         let mut source = input;
         let mut count = 0;
-        while count < self.lower_limit {
+        while count < self.repeat.minimum() {
             let attempt = self.parser.parse(source);
             match attempt {
                 Ok((value, rest)) => {
@@ -166,14 +890,22 @@ impl<'a, T, P> Parser<'a, Vec<T>> for Between<'a, T, P> where P: Parser<'a, T> +
             }
             count += 1;
         }
-        while self.upper_limit.is_bigger_then(count) {
+        while self.repeat.allows_more_than(count) {
             let attempt = self.parser.parse(source);
             match attempt {
+                Ok((_, rest)) if rest.len() == source.len() && self.repeat.maximum().is_none() => {
+                    return Err(ParseError::InfiniteLoopDetected);
+                }
+
                 Ok((value, rest)) => {
                     result.push(value);
                     source = rest;
                 }
 
+                Err(e) if e.is_fatal() => {
+                    return Err(e);
+                }
+
                 Err(_) => {
                     break;
                 }
@@ -185,267 +917,2112 @@ impl<'a, T, P> Parser<'a, Vec<T>> for Between<'a, T, P> where P: Parser<'a, T> +
 }
 
 impl<'a, T, P> Between<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
-    pub fn new(lower_limit: u8, upper_limit: Limit, parser: P) -> Self {
-        Self { lower_limit, upper_limit, parser, phantom: PhantomData }
+    pub fn new(repeat: Repeat, parser: P) -> Self {
+        Self { repeat, parser, phantom: PhantomData }
     }
 }
 
-pub fn between<'a, T>(lower_limit: u8, upper_limit: u8, parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> where T: 'a {
-    Between::new(lower_limit, Limit::At(upper_limit), parser)
+pub fn repeat<'a, T, R>(repeat: R, parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> where T: 'a, R: Into<Repeat> {
+    Between::new(repeat.into(), parser)
+}
+
+pub fn between<'a, T>(lower_limit: usize, upper_limit: usize, parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> where T: 'a {
+    Between::new(Repeat::Between(lower_limit, upper_limit), parser)
 }
 
-pub fn at_least<'a, T>(lower_limit: u8, parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> where T: 'a {
-    Between::new(lower_limit, Limit::Infinity, parser)
+pub fn at_least<'a, T>(lower_limit: usize, parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> where T: 'a {
+    Between::new(Repeat::AtLeast(lower_limit), parser)
 }
 
 pub fn many<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> where T: 'a {
     at_least(0, parser)
 }
 
-pub struct OneOf<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
-    options: Vec<P>,
-    phantom: PhantomData<&'a T>,
+fn count_at_least<'a, T, P>(minimum: usize, parser: P) -> impl Parser<'a, usize> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        let mut rem = input;
+        let mut count = 0;
+        loop {
+            match parser.parse(rem) {
+                Ok((_, rest)) if rest.len() == rem.len() => return Err(ParseError::InfiniteLoopDetected),
+                Ok((_, rest)) => rem = rest,
+                Err(e) if e.is_fatal() || count < minimum => return Err(e),
+                Err(_) => return Ok((count, rem)),
+            }
+            count += 1;
+        }
+    }
+}
+
+pub fn many0_count<'a, T, P>(parser: P) -> impl Parser<'a, usize> where T: 'a, P: Parser<'a, T> + Sized {
+    count_at_least(0, parser)
+}
+
+pub fn many1_count<'a, T, P>(parser: P) -> impl Parser<'a, usize> where T: 'a, P: Parser<'a, T> + Sized {
+    count_at_least(1, parser)
+}
+
+// Repetition for its consumption only: nothing is collected, the result is
+// the text all the repetitions covered.
+pub fn repeat_slice<'a, T, P, R>(repeat: R, parser: P) -> impl Parser<'a, &'a str> where T: 'a, P: Parser<'a, T> + Sized, R: Into<Repeat> {
+    let repeat = repeat.into();
+    move |input: &'a str| {
+        let mut rem = input;
+        let mut count = 0;
+        while repeat.allows_more_than(count) {
+            match parser.parse(rem) {
+                Ok((_, rest)) if rest.len() == rem.len() && repeat.maximum().is_none() => {
+                    return Err(ParseError::InfiniteLoopDetected);
+                }
+                Ok((_, rest)) => rem = rest,
+                Err(e) if e.is_fatal() || count < repeat.minimum() => return Err(e),
+                Err(_) => break,
+            }
+            count += 1;
+        }
+        Ok((&input[..input.len() - rem.len()], rem))
+    }
+}
+
+pub fn many_slice<'a, T, P>(parser: P) -> impl Parser<'a, &'a str> where T: 'a, P: Parser<'a, T> + Sized {
+    repeat_slice(.., parser)
+}
+
+// Options can be any slice-like collection of parsers, so small alternations
+// can be written as arrays and need no allocation.
+pub struct OneOf<'a, T, P, O = Vec<P>> where T: 'a, P: Parser<'a, T> + Sized, O: AsRef<[P]> {
+    options: O,
+    phantom: PhantomData<(&'a T, P)>,
 }
 
-impl<'a, T, P> Parser<'a, T> for OneOf<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
-    fn parse(&self, input: &'a str) -> Result<(T, &'a str), ParseError> {
-        for ref parser in &self.options {
+impl<'a, T, P, O> Parser<'a, T> for OneOf<'a, T, P, O> where T: 'a, P: Parser<'a, T> + Sized, O: AsRef<[P]> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        for parser in self.options.as_ref() {
             let attempt = parser.parse(input);
-            if attempt.is_ok() {
-                return attempt
+            match attempt {
+                Err(ref e) if !e.is_fatal() => {}
+
+                _ => return attempt,
+            }
+        }
+        Err(ParseError::ExpectingOneOfToParse(Found::at(input)))
+    }
+}
+
+impl<'a, T, P, O> OneOf<'a, T, P, O> where T: 'a, P: Parser<'a, T> + Sized, O: AsRef<[P]> {
+    pub fn new(options: O) -> Self {
+        Self { options, phantom: PhantomData }
+    }
+}
+
+pub fn one_of<'a, T, P, O>(options: O) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized, O: AsRef<[P]> {
+    OneOf::new(options)
+}
+
+// Alternation that only tries the branches that can start with the next
+// character. Each character keeps its candidate branches in declaration order,
+// with the `otherwise` branches (those that can start with anything) mixed in.
+pub struct Dispatch<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    branches: Vec<P>,
+    table: HashMap<char, Vec<usize>>,
+    fallback: Vec<usize>,
+    phantom: PhantomData<&'a T>,
+}
+
+impl<'a, T, P> Parser<'a, T> for Dispatch<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        let candidates = input.chars().next()
+            .and_then(|c| self.table.get(&c))
+            .unwrap_or(&self.fallback);
+        for index in candidates {
+            let attempt = self.branches[*index].parse(input);
+            match attempt {
+                Err(ref e) if !e.is_fatal() => {}
+
+                _ => return attempt,
+            }
+        }
+        Err(ParseError::ExpectingOneOfToParse(Found::at(input)))
+    }
+}
+
+impl<'a, T, P> Dispatch<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    pub fn new() -> Self {
+        Self { branches: vec![], table: HashMap::new(), fallback: vec![], phantom: PhantomData }
+    }
+
+    pub fn on<I>(mut self, first: I, parser: P) -> Self where I: IntoIterator<Item = char> {
+        let index = self.branches.len();
+        self.branches.push(parser);
+        for c in first {
+            let fallback = &self.fallback;
+            let candidates = self.table.entry(c).or_insert_with(|| fallback.clone());
+            if candidates.last() != Some(&index) {
+                candidates.push(index);
+            }
+        }
+        self
+    }
+
+    pub fn otherwise(mut self, parser: P) -> Self {
+        let index = self.branches.len();
+        self.branches.push(parser);
+        for candidates in self.table.values_mut() {
+            candidates.push(index);
+        }
+        self.fallback.push(index);
+        self
+    }
+}
+
+impl<'a, T, P> Default for Dispatch<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn dispatch<'a, T, P>() -> Dispatch<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
+    Dispatch::new()
+}
+
+pub fn either<'a, T, P, Q>(first: P, second: Q) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized, Q: Parser<'a, T> + Sized {
+    move |input| {
+        match first.parse(input) {
+            Err(ref e) if !e.is_fatal() => {}
+            attempt => return attempt,
+        }
+        match second.parse(input) {
+            Err(ref e) if !e.is_fatal() => Err(ParseError::ExpectingOneOfToParse(Found::at(input))),
+            attempt => attempt,
+        }
+    }
+}
+
+// Implemented for tuples of parsers, which can all be of different types, and
+// for boxed parsers when there are more options than the tuples go up to.
+pub trait Choice<'a, T> {
+    fn choose(&self, input: &'a str) -> ParseResult<'a, T>;
+}
+
+// Names everything the options expected, unless one of them cannot say.
+fn expecting_one_of(expected: Vec<Option<String>>, input: &str) -> ParseError {
+    let mut names: Vec<String> = vec![];
+    for name in expected {
+        match name {
+            Some(name) if !names.contains(&name) => names.push(name),
+            Some(_) => {}
+            None => return ParseError::ExpectingOneOfToParse(Found::at(input)),
+        }
+    }
+    let name = match names.split_last() {
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+        None => return ParseError::ExpectingOneOfToParse(Found::at(input)),
+    };
+    ParseError::ExpectingNamed(name, Found::at(input))
+}
+
+macro_rules! choice_tuple {
+    ($($parser:ident $index:tt),+) => {
+        impl<'a, T, $($parser),+> Choice<'a, T> for ($($parser,)+) where T: 'a, $($parser: Parser<'a, T>),+ {
+            fn choose(&self, input: &'a str) -> ParseResult<'a, T> {
+                let mut expected = vec![];
+                $(
+                    match self.$index.parse(input) {
+                        Err(e) if !e.is_fatal() => expected.push(e.expected()),
+                        attempt => return attempt,
+                    }
+                )+
+                Err(expecting_one_of(expected, input))
+            }
+        }
+    };
+}
+
+choice_tuple!(P0 0, P1 1);
+choice_tuple!(P0 0, P1 1, P2 2);
+choice_tuple!(P0 0, P1 1, P2 2, P3 3);
+choice_tuple!(P0 0, P1 1, P2 2, P3 3, P4 4);
+choice_tuple!(P0 0, P1 1, P2 2, P3 3, P4 4, P5 5);
+
+impl<'p, 'a, T> Choice<'a, T> for Vec<BoxedParser<'p, 'a, T>> where T: 'a {
+    fn choose(&self, input: &'a str) -> ParseResult<'a, T> {
+        let mut expected = vec![];
+        for parser in self {
+            match parser.parse(input) {
+                Err(e) if !e.is_fatal() => expected.push(e.expected()),
+                attempt => return attempt,
+            }
+        }
+        Err(expecting_one_of(expected, input))
+    }
+}
+
+pub fn choice<'a, T, P>(options: P) -> impl Parser<'a, T> where T: 'a, P: Choice<'a, T> {
+    move |input| options.choose(input)
+}
+
+pub fn skip<'a, T, P>(parser: P) -> impl Parser<'a, ()> where T: 'a, P: Parser<'a, T> + Sized {
+    map(parser, |_|{()})
+}
+
+pub fn space<'a>() -> impl Parser<'a, ()> {
+    skip(one_of([character(' '), character('\t')]))
+}
+
+pub fn spaces<'a>() -> impl Parser<'a, ()> {
+    skip(many(space()))
+}
+
+pub fn newline<'a>() -> impl Parser<'a, ()> {
+    skip(one_of([literal("\n"), literal("\r\n"), literal("\r")]))
+}
+
+pub fn line<'a>() -> impl Parser<'a, &'a str> {
+    move |input: &'a str| {
+        if input.is_empty() {
+            return Err(ParseError::EndOfInput)
+        }
+        let index = input.find(['\n', '\r']).unwrap_or(input.len());
+        Ok((&input[..index], &input[index..]))
+    }
+}
+
+pub fn eol_or_eof<'a>() -> impl Parser<'a, ()> {
+    move |input: &'a str| {
+        if input.is_empty() {
+            return Ok(((), input))
+        }
+        newline().parse(input)
+    }
+}
+
+pub fn number<'a>() -> impl Parser<'a, u16> {
+    map(at_least(1, digit()), to_number)
+}
+
+fn to_number(digits: Vec<char>) -> u16 {
+    let number: String = digits.into_iter().collect();
+    u16::from_str_radix(&number, 10).unwrap_or(0)
+}
+
+pub fn digit<'a>() -> impl Parser<'a, char> {
+    satisfy_named("digit", |c| c.is_ascii_digit())
+}
+
+// Letters stand for the digits past 9 in either case, as with `char::to_digit`,
+// which also panics for a radix above 36.
+pub fn radix_digit<'a>(radix: u32) -> impl Parser<'a, u32> {
+    assert!((2..=36).contains(&radix), "radix must be between 2 and 36, not {}", radix);
+    let name = format!("a base-{} digit", radix);
+    move |input: &'a str| {
+        match next_char(input).and_then(|c| c.to_digit(radix).map(|value| (c, value))) {
+            Some((c, value)) => Ok((value, &input[c.len_utf8()..])),
+            None => Err(ParseError::ExpectingNamed(name.clone(), Found::at(input))),
+        }
+    }
+}
+
+pub fn end<'a, T, P>(parser: P) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input| {
+        parser.parse(input).and_then(|(result, rem)|{
+            if !rem.is_empty() {
+                return Err(ParseError::ExpectingToBeAtEndOfInput(Found::snippet(rem, SNIPPET_LENGTH)))
+            }
+            Ok((result, rem))
+        })
+    }
+}
+
+// Runs `inner` on just the slice `outer` matched, like the inside of a quoted
+// value, and it has to use all of it. The slice is still part of the input,
+// so nodes built inside have spans in terms of the whole source. The end of
+// the slice is not the end of the input, so an inner error that ran into it
+// reports the character that follows the slice instead.
+pub fn parse_within<'a, T, P, Q>(outer: P, inner: Q) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, &'a str> + Sized, Q: Parser<'a, T> + Sized {
+    let inner = end(inner);
+    move |input: &'a str| {
+        let (slice, rem) = outer.parse(input)?;
+        let (result, _) = inner.parse(slice).map_err(|error| {
+            let after = (slice.as_ptr() as usize).checked_sub(input.as_ptr() as usize)
+                .and_then(|start| input.get(start + slice.len()..));
+            match (error.found(), after) {
+                (Some(Found::EndOfInput), Some(after)) => error.with_found(Found::at(after)),
+                _ => error,
+            }
+        })?;
+        Ok((result, rem))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Primary,
+    Alternative,
+}
+
+// For formats with an older syntax that is still accepted: the whole input
+// is parsed with `primary`, and only if that fails, again from the start with
+// `alternative`. When both fail, the primary error is the one reported, as it
+// describes the syntax that should be used.
+pub fn retry_with_alternative<'a, T, P, Q>(primary: P, alternative: Q) -> impl Parser<'a, (Dialect, T)> where T: 'a, P: Parser<'a, T> + Sized, Q: Parser<'a, T> + Sized {
+    let (primary, alternative) = (end(primary), end(alternative));
+    move |input| {
+        match primary.parse(input) {
+            Ok((result, rem)) => Ok(((Dialect::Primary, result), rem)),
+            Err(e) if e.is_fatal() => Err(e),
+            Err(e) => match alternative.parse(input) {
+                Ok((result, rem)) => Ok(((Dialect::Alternative, result), rem)),
+                Err(fatal) if fatal.is_fatal() => Err(fatal),
+                Err(_) => Err(e),
+            },
+        }
+    }
+}
+
+
+pub fn not_followed_by<'a, T, U, P, Q>(parser: P, follower: Q) -> impl Parser<'a, T> where T: 'a, U: 'a, P: Parser<'a, T> + Sized, Q: Parser<'a, U> + Sized {
+    move |input| {
+        parser.parse(input).and_then(|(result, rem)|{
+            if follower.parse(rem).is_ok() {
+                return Err(ParseError::UnexpectedFollowingInput(Found::snippet(rem, SNIPPET_LENGTH)))
+            }
+            Ok((result, rem))
+        })
+    }
+}
+
+pub fn followed_by<'a, T, U, P, Q>(parser: P, follower: Q) -> impl Parser<'a, T> where T: 'a, U: 'a, P: Parser<'a, T> + Sized, Q: Parser<'a, U> + Sized {
+    move |input| {
+        parser.parse(input).and_then(|(result, rem)|{
+            follower.parse(rem)?;
+            Ok((result, rem))
+        })
+    }
+}
+
+// Runs `parser` on at most the next `max_chars` characters, plus one more so
+// it can see where what it parses ends. Consuming more than `max_chars`, or
+// failing for want of input past the window, means the parser needed to look
+// further than allowed, which is reported as an ordinary failure so that
+// alternatives can still be tried.
+pub fn attempt_within<'a, T, P>(parser: P, max_chars: usize) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        let mut limits = input.char_indices().map(|(i, _)| i).skip(max_chars);
+        let allowed = limits.next().unwrap_or(input.len());
+        let window = &input[..limits.next().unwrap_or(input.len())];
+        let exceeded = ParseError::LookaheadExceeded(max_chars);
+        match parser.parse(window) {
+            Ok((result, rest)) if window.len() - rest.len() <= allowed => Ok((result, &input[window.len() - rest.len()..])),
+            Ok(_) => Err(exceeded),
+            Err(e) if window.len() < input.len() && (e == ParseError::EndOfInput || matches!(e.found(), Some(Found::EndOfInput))) => Err(exceeded),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+// The failure is where the error's `Found` was taken from, as long as that
+// lies in `input` and shows what the error says it found. An error made some
+// other way is placed at the start of the input.
+fn failure_offset(input: &str, error: &ParseError) -> usize {
+    let rest = FOUND_AT.with(Cell::get).checked_sub(input.as_ptr() as usize).and_then(|offset| input.get(offset..));
+    match (error.found(), rest) {
+        (Some(Found::EndOfInput), _) => input.len(),
+        (Some(Found::Character(c)), Some(rest)) if rest.starts_with(*c) => input.len() - rest.len(),
+        (Some(Found::Text(text)), Some(rest)) if rest.starts_with(text.as_str()) => input.len() - rest.len(),
+        _ => 0,
+    }
+}
+
+// Attaches up to `chars` characters around the failure, half of them before
+// it where there are that many.
+pub fn with_span_context<'a, T, P>(parser: P, chars: usize) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        parser.parse(input).map_err(|error| {
+            let offset = failure_offset(input, &error);
+            let before: usize = input[..offset].chars().rev().take(chars / 2).map(char::len_utf8).sum();
+            let context = input[offset - before..].chars().take(chars).collect();
+            ParseError::InContext(Box::new(error), context)
+        })
+    }
+}
+
+// Once a parser has got far enough to know what it is looking at, a failure
+// after that point is the real error. Committed errors count as fatal, so
+// alternatives and repetitions pass them up instead of trying something else;
+// `recover` is what stops them.
+pub fn cut<'a, T, P>(parser: P) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized {
+    move |input| {
+        parser.parse(input).map_err(|e| match e {
+            e if e.is_fatal() => e,
+            e => ParseError::Committed(Box::new(e)),
+        })
+    }
+}
+
+pub fn cut_after<'a, A, B, P, Q>(prefix: P, parser: Q) -> impl Parser<'a, B> where A: 'a, B: 'a, P: Parser<'a, A> + Sized, Q: Parser<'a, B> + Sized {
+    let parser = cut(parser);
+    move |input| {
+        let (_, rem) = prefix.parse(input)?;
+        parser.parse(rem)
+    }
+}
+
+pub fn separated_pair<'a, A, S, B, P, Q, R>(first: P, separator: Q, second: R) -> impl Parser<'a, (A, B)> where A: 'a, S: 'a, B: 'a, P: Parser<'a, A> + Sized, Q: Parser<'a, S> + Sized, R: Parser<'a, B> + Sized {
+    move |input| {
+        let (a, rem) = first.parse(input)?;
+        let (_, rem) = separator.parse(rem)?;
+        let (b, rem) = second.parse(rem)?;
+        Ok(((a, b), rem))
+    }
+}
+
+// `a b a b a`: starts and ends with the first parser unless told otherwise.
+// When a trailing second is not allowed, one without a first after it is
+// left unconsumed.
+pub struct Alternating<'a, A, B, P, Q> where A: 'a, B: 'a, P: Parser<'a, A> + Sized, Q: Parser<'a, B> + Sized {
+    first: P,
+    second: Q,
+    leading_second: bool,
+    trailing_second: bool,
+    phantom: PhantomData<&'a (A, B)>,
+}
+
+impl<'a, A, B, P, Q> Parser<'a, (Vec<A>, Vec<B>)> for Alternating<'a, A, B, P, Q> where A: 'a, B: 'a, P: Parser<'a, A> + Sized, Q: Parser<'a, B> + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, (Vec<A>, Vec<B>)> {
+        let mut firsts = vec![];
+        let mut seconds = vec![];
+        let mut rem = input;
+        let mut before_second = input;
+        if self.leading_second {
+            if let Ok((b, rest)) = self.second.parse(rem) {
+                seconds.push(b);
+                rem = rest;
+            }
+        }
+        loop {
+            let (a, rest) = match self.first.parse(rem) {
+                Ok(parsed) => parsed,
+                Err(e) if e.is_fatal() || firsts.is_empty() => return Err(e),
+                Err(_) if self.trailing_second => return Ok(((firsts, seconds), rem)),
+                Err(_) => {
+                    seconds.pop();
+                    return Ok(((firsts, seconds), before_second));
+                }
+            };
+            firsts.push(a);
+            let round = rem.len();
+            rem = rest;
+            before_second = rem;
+            match self.second.parse(rem) {
+                Ok((_, rest)) if rest.len() == round => return Err(ParseError::InfiniteLoopDetected),
+                Ok((b, rest)) => {
+                    seconds.push(b);
+                    rem = rest;
+                }
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(_) => return Ok(((firsts, seconds), rem)),
+            }
+        }
+    }
+}
+
+impl<'a, A, B, P, Q> Alternating<'a, A, B, P, Q> where A: 'a, B: 'a, P: Parser<'a, A> + Sized, Q: Parser<'a, B> + Sized {
+    pub fn new(first: P, second: Q) -> Self {
+        Self { first, second, leading_second: false, trailing_second: false, phantom: PhantomData }
+    }
+
+    pub fn leading_second(mut self) -> Self {
+        self.leading_second = true;
+        self
+    }
+
+    pub fn trailing_second(mut self) -> Self {
+        self.trailing_second = true;
+        self
+    }
+}
+
+pub fn alternating<'a, A, B, P, Q>(first: P, second: Q) -> Alternating<'a, A, B, P, Q> where A: 'a, B: 'a, P: Parser<'a, A> + Sized, Q: Parser<'a, B> + Sized {
+    Alternating::new(first, second)
+}
+
+// Implemented for tuples of parsers so `permutation` can hand back a tuple of
+// differently typed results.
+pub trait Permutation<'a, T> {
+    fn permute(&self, input: &'a str) -> ParseResult<'a, T>;
+}
+
+// Each pass tries the parsers that have not matched yet in declaration order;
+// a pass where none of them match ends the parse.
+macro_rules! permutation_tuple {
+    ($($parser:ident $output:ident $index:tt),+) => {
+        impl<'a, $($output, $parser),+> Permutation<'a, ($($output,)+)> for ($($parser,)+) where $($output: 'a, $parser: Parser<'a, $output>),+ {
+            fn permute(&self, input: &'a str) -> ParseResult<'a, ($($output,)+)> {
+                let mut results = ($(None::<$output>,)+);
+                let mut rem = input;
+                loop {
+                    let before = rem.len();
+                    let mut matched = false;
+                    $(
+                        if results.$index.is_none() {
+                            match self.$index.parse(rem) {
+                                Ok((value, rest)) => {
+                                    results.$index = Some(value);
+                                    rem = rest;
+                                    matched = true;
+                                }
+                                Err(e) if e.is_fatal() => return Err(e),
+                                Err(_) => {}
+                            }
+                        }
+                    )+
+                    if $(results.$index.is_some())&&+ {
+                        return Ok((($(results.$index.take().expect("every parser to have matched"),)+), rem));
+                    }
+                    if !matched {
+                        return Err(ParseError::ExpectingOneOfToParse(Found::at(&input[input.len() - before..])));
+                    }
+                }
+            }
+        }
+    };
+}
+
+permutation_tuple!(P0 T0 0, P1 T1 1);
+permutation_tuple!(P0 T0 0, P1 T1 1, P2 T2 2);
+permutation_tuple!(P0 T0 0, P1 T1 1, P2 T2 2, P3 T3 3);
+permutation_tuple!(P0 T0 0, P1 T1 1, P2 T2 2, P3 T3 3, P4 T4 4);
+permutation_tuple!(P0 T0 0, P1 T1 1, P2 T2 2, P3 T3 3, P4 T4 4, P5 T5 5);
+
+pub fn permutation<'a, T, P>(parsers: P) -> impl Parser<'a, T> where T: 'a, P: Permutation<'a, T> {
+    move |input| parsers.permute(input)
+}
+
+// Items with any whitespace around them and the separators between them.
+// The list may be empty and may end with a separator; it ends before
+// whatever is not an item, so the caller reports what should have come next.
+pub fn ws_delimited_list<'a, T, P>(item: P, separator: char) -> impl Parser<'a, Vec<T>> where T: 'a, P: Parser<'a, T> + Sized {
+    let whitespace = || take_while(char::is_whitespace);
+    move |input: &'a str| {
+        let mut items = vec![];
+        let mut rem = input;
+        loop {
+            let (_, start) = whitespace().parse(rem)?;
+            let (value, rest) = match item.parse(start) {
+                Ok(parsed) => parsed,
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(_) => return Ok((items, rem)),
+            };
+            items.push(value);
+            let (_, after) = whitespace().parse(rest)?;
+            match after.strip_prefix(separator) {
+                Some(next) => rem = next,
+                None => return Ok((items, rest)),
+            }
+        }
+    }
+}
+
+pub fn bracketed_list<'a, T, P>(open: char, item: P, separator: char, close: char) -> impl Parser<'a, Vec<T>> where T: 'a, P: Parser<'a, T> + Sized {
+    let items = ws_delimited_list(item, separator);
+    move |input: &'a str| {
+        let (_, rem) = character(open).parse(input)?;
+        let (items, rem) = items.parse(rem)?;
+        let (_, rem) = take_while(char::is_whitespace).parse(rem)?;
+        match rem.strip_prefix(close) {
+            Some(rem) => Ok((items, rem)),
+            None => Err(ParseError::ExpectingNamed(format!("{:?} to close {:?}", close, open), Found::at(rem))),
+        }
+    }
+}
+
+// Parsers only see the remaining input, so a boundary is anything that is not
+// followed by a word character.
+pub fn word_boundary<'a>() -> impl Parser<'a, ()> {
+    move |input: &'a str| {
+        match input.chars().next() {
+            Some(c) if c.is_alphanumeric() || c == '_' => Err(ParseError::ExpectingWordBoundary(Found::Character(c))),
+            _ => Ok(((), input)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct TrieNode {
+    children: HashMap<char, usize>,
+    terminal: bool,
+}
+
+// All keywords share one trie, so matching walks the input once and picks
+// the longest keyword that ends on a word boundary, whatever order they were
+// given in.
+pub struct Keywords {
+    nodes: Vec<TrieNode>,
+    words: Vec<String>,
+}
+
+impl<'a> Parser<'a, &'a str> for Keywords {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        let mut node = 0;
+        let mut longest = None;
+        for (offset, c) in input.char_indices() {
+            match self.nodes[node].children.get(&c) {
+                Some(next) => node = *next,
+                None => break,
+            }
+            let end = offset + c.len_utf8();
+            if self.nodes[node].terminal && word_boundary().parse(&input[end..]).is_ok() {
+                longest = Some(end);
             }
         }
-        Err(ParseError::ExpectingOneOfToParse)
+        match longest {
+            Some(end) => Ok((&input[..end], &input[end..])),
+            None => Err(self.suggest(input).unwrap_or_else(|| ParseError::ExpectingOneOfToParse(Found::at(input)))),
+        }
+    }
+}
+
+impl Keywords {
+    pub fn new<I, S>(keywords: I) -> Self where I: IntoIterator<Item = S>, S: AsRef<str> {
+        let mut nodes = vec![TrieNode::default()];
+        let mut words = vec![];
+        for keyword in keywords {
+            words.push(keyword.as_ref().to_owned());
+            let mut node = 0;
+            for c in keyword.as_ref().chars() {
+                node = match nodes[node].children.get(&c) {
+                    Some(next) => *next,
+                    None => {
+                        nodes.push(TrieNode::default());
+                        let next = nodes.len() - 1;
+                        nodes[node].children.insert(c, next);
+                        next
+                    }
+                };
+            }
+            nodes[node].terminal = true;
+        }
+        Self { nodes, words }
+    }
+
+    // The word at the start of the input is probably a misspelled keyword if
+    // it is one edit away from a keyword of four or more characters, or two
+    // from one of eight or more. Anything further is more likely an ordinary
+    // word, like `letter` next to `letrec`. The closest keyword wins, and the
+    // one given first among equally close ones.
+    fn suggest(&self, input: &str) -> Option<ParseError> {
+        let length = input.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(input.len());
+        let word = &input[..length];
+        if word.is_empty() {
+            return None;
+        }
+        self.words.iter()
+            .map(|keyword| (edit_distance(word, keyword), keyword))
+            .filter(|(distance, keyword)| *distance <= (keyword.chars().count() / 4).min(2))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, keyword)| ParseError::Misspelled(keyword.clone(), Found::Text(word.to_owned())))
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != *y);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+pub fn keywords<I, S>(keywords: I) -> Keywords where I: IntoIterator<Item = S>, S: AsRef<str> {
+    Keywords::new(keywords)
+}
+
+// A keyword only where the grammar asks for one: the text is read as a
+// whole identifier and accepted if it is the keyword, so `select` can still
+// be an ordinary identifier everywhere else and `selected` never matches.
+pub fn contextual_keyword<'a, 'k, P>(identifier: P, keyword: &'k str) -> impl Parser<'a, &'a str> + 'k where P: Parser<'a, &'a str> + Sized + 'k {
+    move |input: &'a str| {
+        let (text, rem) = identifier.parse(input)?;
+        if text != keyword {
+            return Err(ParseError::ExpectingLiteral(keyword.to_owned(), Found::Text(text.to_owned())));
+        }
+        Ok((text, rem))
     }
 }
 
-impl<'a, T, P> OneOf<'a, T, P> where T: 'a, P: Parser<'a, T> + Sized {
-    pub fn new(options: Vec<P>) -> Self {
-        Self { options, phantom: PhantomData }
+pub fn escaped<'a, N, E, P, Q>(normal: P, escape: char, escapable: Q) -> impl Parser<'a, &'a str> where N: 'a, E: 'a, P: Parser<'a, N> + Sized, Q: Parser<'a, E> + Sized {
+    move |input: &'a str| {
+        let mut rem = input;
+        loop {
+            if rem.starts_with(escape) {
+                let (_, rest) = escapable.parse(&rem[escape.len_utf8()..])?;
+                rem = rest;
+            } else {
+                match normal.parse(rem) {
+                    Ok((_, rest)) if rest.len() < rem.len() => rem = rest,
+                    _ => break,
+                }
+            }
+        }
+        let consumed = input.len() - rem.len();
+        Ok((&input[..consumed], rem))
+    }
+}
+
+pub fn escaped_transform<'a, N, E, P, Q>(normal: P, escape: char, transform: Q) -> impl Parser<'a, String> where N: 'a, E: 'a, P: Parser<'a, N> + Sized, Q: Parser<'a, E> + Sized, String: Extend<N> + Extend<E> {
+    move |input: &'a str| {
+        let mut result = String::new();
+        let mut rem = input;
+        loop {
+            if rem.starts_with(escape) {
+                let (value, rest) = transform.parse(&rem[escape.len_utf8()..])?;
+                result.extend(Some(value));
+                rem = rest;
+            } else {
+                match normal.parse(rem) {
+                    Ok((value, rest)) if rest.len() < rem.len() => {
+                        result.extend(Some(value));
+                        rem = rest;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        Ok((result, rem))
+    }
+}
+
+
+pub struct Records<'a, T, P> where P: Parser<'a, T> + Sized {
+    parser: P,
+    input: &'a str,
+    phantom: PhantomData<T>,
+}
+
+impl<'a, T, P> Iterator for Records<'a, T, P> where P: Parser<'a, T> + Sized {
+    type Item = Result<T, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+        let (record, rest) = match self.input.find('\n') {
+            Some(index) => (&self.input[..index], &self.input[index + 1..]),
+            None => (self.input, ""),
+        };
+        self.input = rest;
+        let record = record.strip_suffix('\r').unwrap_or(record);
+        let attempt = self.parser.parse(record).and_then(|(result, rem)|{
+            if !rem.is_empty() {
+                return Err(ParseError::ExpectingToBeAtEndOfInput(Found::snippet(rem, SNIPPET_LENGTH)))
+            }
+            Ok(result)
+        });
+        Some(attempt)
+    }
+}
+
+impl<'a, T, P> Records<'a, T, P> where P: Parser<'a, T> + Sized {
+    pub fn new(parser: P, input: &'a str) -> Self {
+        Self { parser, input, phantom: PhantomData }
+    }
+}
+
+pub fn parse_records<'a, T, P>(parser: P, input: &'a str) -> Records<'a, T, P> where P: Parser<'a, T> + Sized {
+    Records::new(parser, input)
+}
+
+
+#[macro_export]
+macro_rules! sequence {
+    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
+        |input| {
+            let rem = input;
+            $(
+                let ($name, rem) = $parser.parse(rem)?;
+            )*
+            let result = $finish;
+            Ok((result, rem))
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! move_sequence {
+    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
+        move |input| {
+            let rem = input;
+            $(
+                let ($name, rem) = $parser.parse(rem)?;
+            )*
+            let result = $finish;
+            Ok((result, rem))
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! seq {
+    ($($binding:pat = $parser:expr;)+ => $finish:expr ) => {{
+        |input| {
+            let rem = input;
+            $(
+                let ($binding, rem) = $parser.parse(rem)?;
+            )*
+            let result = $finish;
+            Ok((result, rem))
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! move_seq {
+    ($($binding:pat = $parser:expr;)+ => $finish:expr ) => {{
+        move |input| {
+            let rem = input;
+            $(
+                let ($binding, rem) = $parser.parse(rem)?;
+            )*
+            let result = $finish;
+            Ok((result, rem))
+        }
+    }};
+}
+
+// Unlike one_of, the options can all be different parser types. Up to six
+// are put in a tuple so no boxing is needed; more than that are boxed.
+#[macro_export]
+macro_rules! choice {
+    ($parser:expr $(,)?) => {
+        $parser
+    };
+    ($p0:expr, $p1:expr $(,)?) => {
+        $crate::framework::choice(($p0, $p1))
+    };
+    ($p0:expr, $p1:expr, $p2:expr $(,)?) => {
+        $crate::framework::choice(($p0, $p1, $p2))
+    };
+    ($p0:expr, $p1:expr, $p2:expr, $p3:expr $(,)?) => {
+        $crate::framework::choice(($p0, $p1, $p2, $p3))
+    };
+    ($p0:expr, $p1:expr, $p2:expr, $p3:expr, $p4:expr $(,)?) => {
+        $crate::framework::choice(($p0, $p1, $p2, $p3, $p4))
+    };
+    ($p0:expr, $p1:expr, $p2:expr, $p3:expr, $p4:expr, $p5:expr $(,)?) => {
+        $crate::framework::choice(($p0, $p1, $p2, $p3, $p4, $p5))
+    };
+    ($($parser:expr),+ $(,)?) => {
+        $crate::framework::choice(vec![$($crate::framework::boxed($parser)),+])
+    };
+}
+
+#[macro_export]
+macro_rules! sequence_skipping {
+    ($junk:expr; $(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
+        |input| {
+            let rem = input;
+            $(
+                let (_, rem) = $junk.parse(rem)?;
+                let ($name, rem) = $parser.parse(rem)?;
+            )*
+            let (_, rem) = $junk.parse(rem)?;
+            let result = $finish;
+            Ok((result, rem))
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! move_sequence_skipping {
+    ($junk:expr; $(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
+        move |input| {
+            let rem = input;
+            $(
+                let (_, rem) = $junk.parse(rem)?;
+                let ($name, rem) = $parser.parse(rem)?;
+            )*
+            let (_, rem) = $junk.parse(rem)?;
+            let result = $finish;
+            Ok((result, rem))
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! sequence_ignore_spaces {
+    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {
+        $crate::sequence_skipping!{ $crate::framework::spaces(); $(let $name = $parser),+ => $finish }
+    };
+}
+
+#[macro_export]
+macro_rules! move_sequence_ignore_spaces {
+    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {
+        $crate::move_sequence_skipping!{ $crate::framework::spaces(); $(let $name = $parser),+ => $finish }
+    };
+}
+
+pub fn blank_lines<'a>() -> impl Parser<'a, ()> {
+    skip(many(blank_line()))
+}
+
+pub fn blank_line<'a>() -> impl Parser<'a, ()> {
+    sequence!{
+        let _spaces = spaces(),
+        let _newline = newline()
+        =>
+        ()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_rewinds_to_a_checkpoint() {
+        let mut cursor = Cursor::new("AB");
+        let checkpoint = cursor.checkpoint();
+
+        let first = cursor.parse(&character('A'));
+        let second = cursor.parse(&character('C'));
+        cursor.rewind(checkpoint);
+
+        assert_eq!(first, Ok('A'));
+        assert_eq!(second, Err(ParseError::ExpectingCharacter('C', Found::Character('B'))));
+        assert_eq!(cursor.offset(), 0);
+        assert_eq!(cursor.rest(), "AB");
+    }
+
+    #[test]
+    fn cursor_supports_custom_backtracking() {
+        let both_or_none = |input| {
+            let mut cursor = Cursor::new(input);
+            let checkpoint = cursor.checkpoint();
+            let attempt = cursor.parse(&character('A')).and_then(|a| {
+                cursor.parse(&character('B')).map(|b| Some((a, b)))
+            });
+            let result = attempt.unwrap_or_else(|_| {
+                cursor.rewind(checkpoint);
+                None
+            });
+            Ok((result, cursor.rest()))
+        };
+
+        let actual = both_or_none.parse("AC");
+
+        let expected = Ok((None, "AC"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_a_character() {
+        let input = "ABCD";
+        let parser = character('A');
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(('A', "BCD"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn error_reports_what_was_found() {
+        let input = "BCD";
+        let parser = character('A');
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingCharacter('A', Found::Character('B')));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.unwrap_err().to_string(), "expected 'A', found 'B'");
+    }
+
+    #[test]
+    fn literal_error_reports_a_snippet() {
+        let input = "-> FF";
+        let parser = literal("=>");
+
+        let actual = parser.parse(input).unwrap_err().to_string();
+
+        assert_eq!(actual, r#"expected "=>", found "->""#);
+    }
+
+    #[test]
+    fn parse_any_digit() {
+        let input = "0123";
+        let parser = any(|c: char| c.is_ascii_digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(('0', "123"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_any_digit_as_number() {
+        let input = "1230";
+        let parser = map(
+          any(|c: char| c.is_ascii_digit()), 
+          |c: char| c.to_digit(10).unwrap_or(0));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((1, "230"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn named_predicate_reports_its_name() {
+        let input = "x1";
+        let parser = satisfy_named("digit", |c: char| c.is_ascii_digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("digit".to_owned(), Found::Character('x')));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.unwrap_err().to_string(), "expected digit, found 'x'");
+    }
+
+    #[test]
+    fn parse_literal_arrow() {
+        let input = "=> FF";
+        let parser = literal("=>");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("=>", " FF"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_take_while_digits() {
+        let input = "123abc";
+        let parser = take_while(|c: char| c.is_ascii_digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("123", "abc"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_take_till_separator() {
+        let input = "key=value";
+        let parser = take_till(|c: char| c == '=');
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("key", "=value"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_take_until_delimiter() {
+        let input = "comment */ code";
+        let parser = take_until("*/");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("comment ", "*/ code"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_take_until_and_consume_delimiter() {
+        let input = "comment */ code";
+        let parser = take_until_and_consume("*/");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((("comment ", "*/"), " code"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn take_until_should_fail_without_delimiter() {
+        let input = "comment";
+        let parser = take_until_and_consume("*/");
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingLiteral("*/".to_owned(), Found::EndOfInput));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn parse_regex_date() {
+        let input = "2020-04 rest";
+        let parser = regex(r"[0-9]{4}-[0-9]{2}");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("2020-04", " rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_should_only_match_at_the_start() {
+        let input = "x 2020-04";
+        let parser = regex(r"[0-9]{4}-[0-9]{2}");
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingPattern("[0-9]{4}-[0-9]{2}".to_owned(), Found::Text("x 2020-04".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn parse_regex_captures() {
+        let input = "2020-04 rest";
+        let parser = regex_captures(r"([0-9]{4})-([0-9]{2})?");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((vec![Some("2020-04"), Some("2020"), Some("04")], " rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_glob_with_star() {
+        let input = "notes.backup.txt rest";
+        let parser = glob("*.txt");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((("notes.backup.txt", vec!["notes.backup"]), " rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_glob_with_question_marks() {
+        let input = "user-42";
+        let parser = glob("user-??");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((("user-42", vec!["4", "2"]), ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn glob_should_fail_on_mismatch() {
+        let input = "notes.md";
+        let parser = glob("*.txt");
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingPattern("*.txt".to_owned(), Found::Text("notes.md".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_between_2_and_4_digits() {
+        let input = "12345";
+        let parser = between(2, 4, any(|c: char| c.is_ascii_digit()));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((vec!['1', '2', '3', '4'], "5"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn many_should_detect_zero_width_loops() {
+        let input = "abc";
+        let parser = many(take_while(|c: char| c.is_ascii_digit()));
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::InfiniteLoopDetected);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_one_of_a_or_b() {
+        let input = "a1";
+        let parser = one_of(vec![character('a'), character('b')]);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(('a', "1"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_skip_many_spaces() {
+        let input = "           next";
+        let parser = skip(many(character(' ')));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(((), "next"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_spaces() {
+        let input = "          \tnext";
+        let parser = spaces();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(((), "next"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_newline() {
+        let input = "\n";
+        let parser = newline();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(((), ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_crlf_newline() {
+        let input = "\r\nnext";
+        let parser = newline();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(((), "next"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_line() {
+        let input = "key = value\r\nnext";
+        let parser = line();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("key = value", "\r\nnext"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_last_line_without_newline() {
+        let input = "last";
+        let parser = sequence!{
+            let content = line(),
+            let _end = eol_or_eof()
+            =>
+            content
+        };
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("last", ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Assign<'a> {
+        name: &'a str,
+        value: u16,
+    }
+
+    #[test]
+    fn parse_seq_with_bindings_and_ignored_results() {
+        let input = "x=12;";
+        let parser = seq!{
+            name = take_while(|c| c.is_alphabetic());
+            _ = character('=');
+            value = number();
+            => Assign { name, value }
+        };
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((Assign { name: "x", value: 12 }, ";"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_choice_of_different_parser_types() {
+        let input = "abc";
+        let parser = choice!(
+            map(character('x'), |c| c.to_string()),
+            map(literal("ab"), |s| s.to_owned()),
+            map(any(|_| true), |c| c.to_string()),
+        );
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("ab".to_owned(), "c"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn choice_reports_the_input_none_of_its_options_matched() {
+        let input = "z";
+        let parser = choice!(character('x'), character('y'), digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("'x', 'y' or digit".to_owned(), Found::Character('z')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn choice_boxes_options_past_six() {
+        let inputs = ["g", "7", "z"];
+        let parser = choice!(
+            character('a'), character('b'), character('c'), character('d'),
+            character('e'), character('f'), character('g'), digit(),
+        );
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![
+            Ok(('g', "")),
+            Ok(('7', "")),
+            Err(ParseError::ExpectingNamed("'a', 'b', 'c', 'd', 'e', 'f', 'g' or digit".to_owned(), Found::Character('z'))),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn skip_until_leaves_the_match_unconsumed() {
+        let input = "junk, 42 rest";
+        let parser = skip_until(number());
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("junk, ", "42 rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn skip_until_and_consume_returns_the_match() {
+        let input = "é, 42 rest";
+        let parser = skip_until_and_consume(number());
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((("é, ", 42), " rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn skip_until_fails_when_nothing_matches() {
+        let input = "abc";
+        let parser = skip_until(digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("digit".to_owned(), Found::EndOfInput));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn collect_string_joins_repeated_characters() {
+        let input = "abc1";
+        let parser = collect_string(many(any(char::is_alphabetic)));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("abc".to_owned(), "1"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn recognize_returns_the_consumed_text() {
+        let input = "ab12c";
+        let parser = recognize(seq!{
+            _letters = many(any(char::is_alphabetic));
+            _digits = at_least(1, digit());
+            => ()
+        });
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("ab12", "c"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn many_slice_returns_the_repeated_text() {
+        let input = "abab!";
+        let parser = many_slice(literal("ab"));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("abab", "!"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn repeat_slice_stops_at_its_upper_bound() {
+        let input = "12345";
+        let parser = repeat_slice(2..=3, digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("123", "45"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn repeat_slice_requires_its_lower_bound() {
+        let input = "1a";
+        let parser = repeat_slice(2.., digit());
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("digit".to_owned(), Found::Character('a')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn boxed_parsers_of_different_types_can_be_alternatives() {
+        let input = "42";
+        let keywords = ["let", "fn"];
+        let mut options: Vec<BoxedParser<&str>> = keywords.iter().map(|keyword| boxed(literal(keyword))).collect();
+        options.push(boxed(take_while(|c| c.is_ascii_digit())));
+        let parser = one_of(options);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("42", ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn boxed_parser_from_a_trait_object() {
+        let input = "x";
+        let object: Box<dyn Parser<char>> = Box::new(character('x'));
+        let parser = BoxedParser::from(object);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(('x', ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn one_of_accepts_an_array_of_options() {
+        let input = "b";
+        let parser = one_of([character('a'), character('b')]);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(('b', ""));
+        assert_eq!(actual, expected);
+    }
+
+    fn statement<'a>() -> impl Parser<'a, &'a str> {
+        dispatch()
+            .on("l".chars(), boxed(literal("let")))
+            .on("l".chars(), boxed(literal("loop")))
+            .on('0'..='9', boxed(take_while(|c| c.is_ascii_digit())))
+            .otherwise(boxed(take_while(char::is_alphabetic)))
+    }
+
+    #[test]
+    fn dispatch_tries_branches_for_the_first_character() {
+        let input = "loop";
+        let parser = statement();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("loop", ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn dispatch_falls_back_for_other_characters() {
+        let inputs = ["12a", "xyz1", ""];
+        let parser = statement();
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![Ok(("12", "a")), Ok(("xyz", "1")), Ok(("", ""))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn dispatch_keeps_declaration_order_with_fallbacks() {
+        let input = "lx";
+        let parser = dispatch()
+            .otherwise(boxed(literal("lx")))
+            .on("l".chars(), boxed(literal("l")));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("lx", ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn keywords_match_the_longest_keyword() {
+        let inputs = ["letrec f", "let x", "lambda."];
+        let parser = keywords(["let", "letrec", "lambda"]);
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![Ok(("letrec", " f")), Ok(("let", " x")), Ok(("lambda", "."))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn keywords_must_end_on_a_word_boundary() {
+        let inputs = ["letter", "selected"];
+        let parser = keywords(["let", "letrec", "select"]);
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![Err(ParseError::ExpectingOneOfToParse(Found::Character('l'))), Err(ParseError::ExpectingOneOfToParse(Found::Character('s')))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn character_and_any_step_over_multibyte_characters() {
+        let input = "éa€";
+        let parser = seq!{
+            first = character('é');
+            second = any(|c| c.is_ascii());
+            third = any(|_| true);
+            => (first, second, third)
+        };
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((('é', 'a', '€'), ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn take_while_handles_mixed_input() {
+        let input = "abcé€d1";
+        let parser = take_while(char::is_alphabetic);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("abcé", "€d1"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_measured_reports_consumed_bytes_and_end_offset() {
+        let source = "ab€1;";
+        let parser = literal("€1");
+
+        let actual = parser.parse_measured(&source[2..]);
+
+        let expected = Ok(Parsed { value: "€1", rest: ";", consumed: 4 });
+        assert_eq!(actual, expected);
+        assert_eq!(actual.map(|parsed| parsed.end(source)), Ok(6));
+    }
+
+    #[test]
+    fn errors_describe_their_code_and_expectation() {
+        let error = ParseError::ExpectingNamed("digit".to_owned(), Found::Character('x'));
+
+        assert_eq!(error.code(), "E0004");
+        assert_eq!(error.expected(), Some("digit".to_owned()));
+        assert_eq!(error.found(), Some(&Found::Character('x')));
+    }
+
+    struct German;
+
+    impl Phrasing for German {
+        fn expected(&self, error: &ParseError) -> Option<String> {
+            match error {
+                ParseError::ExpectingNamed(name, _) if name == "digit" => Some("eine Ziffer".to_owned()),
+                _ => error.expected(),
+            }
+        }
+
+        fn found(&self, found: &Found) -> String {
+            match found {
+                Found::EndOfInput => "das Ende der Eingabe".to_owned(),
+                _ => found.to_string(),
+            }
+        }
+
+        fn message(&self, error: &ParseError) -> String {
+            match (self.expected(error), error.found()) {
+                (Some(expected), Some(found)) => format!("{} erwartet, {} gefunden", expected, self.found(found)),
+                _ => error.to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn errors_can_be_phrased_differently() {
+        let error = ParseError::ExpectingNamed("digit".to_owned(), Found::EndOfInput);
+
+        assert_eq!(error.render_with(&DefaultPhrasing), error.to_string());
+        assert_eq!(error.render_with(&German), "eine Ziffer erwartet, das Ende der Eingabe gefunden");
+    }
+
+    #[test]
+    fn parse_separated_pair() {
+        let input = "step:8\n";
+        let parser = separated_pair(take_while(char::is_alphabetic), character(':'), number());
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((("step", 8), "\n"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn repeat_converts_from_ranges() {
+        assert_eq!(Repeat::from(3..=3), Repeat::Exactly(3));
+        assert_eq!(Repeat::from(2..), Repeat::AtLeast(2));
+        assert_eq!(Repeat::from(..5), Repeat::AtMost(4));
+        assert_eq!(Repeat::from(1..=4), Repeat::Between(1, 4));
+    }
+
+    #[test]
+    #[should_panic(expected = "repetition range 5..3 is empty")]
+    fn repeat_rejects_inverted_ranges() {
+        let _ = Repeat::from(std::ops::Range { start: 5, end: 3 });
+    }
+
+    #[test]
+    fn parse_repeat_with_a_range() {
+        let input = "aaab";
+        let parser = repeat(..=2, character('a'));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((vec!['a', 'a'], "ab"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn many_is_not_limited_in_count() {
+        let input = "a".repeat(1_000);
+        let parser = many(character('a'));
+
+        let actual = parser.parse(&input).map(|(characters, rem)| (characters.len(), rem));
+
+        let expected = Ok((1_000, ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn many0_count_counts_matches() {
+        let input = "    indented";
+        let parser = many0_count(character(' '));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((4, "indented"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn many1_count_requires_a_match() {
+        let input = "x";
+        let parser = many1_count(character('#'));
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingCharacter('#', Found::Character('x')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn alternating_collects_operands_and_operators() {
+        let input = "1+2-3+";
+        let parser = alternating(number(), one_of([character('+'), character('-')]));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(((vec![1, 2, 3], vec!['+', '-']), "+"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn alternating_can_start_and_end_with_the_second_parser() {
+        let input = "|a|b|";
+        let parser = alternating(any(char::is_alphabetic), character('|')).leading_second().trailing_second();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(((vec!['a', 'b'], vec!['|', '|', '|']), ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn attempt_within_parses_when_the_decision_fits_the_window() {
+        let input = "12+3";
+        let parser = attempt_within(number(), 3);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((12, "+3"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn attempt_within_accepts_an_exact_fit() {
+        let inputs = ["12+3", "12"];
+        let parser = attempt_within(number(), 2);
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![Ok((12, "+3")), Ok((12, ""))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn attempt_within_gives_up_when_the_parser_looks_further() {
+        let input = "(((((x)))))";
+        let parser = either(attempt_within(literal("(((((x)))))"), 4), literal("("));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("(", "((((x)))))"));
+        assert_eq!(actual, expected);
+        assert_eq!(attempt_within(number(), 2).parse("123"), Err(ParseError::LookaheadExceeded(2)));
+    }
+
+    #[test]
+    fn permutation_parses_each_parser_once_in_any_order() {
+        let input = "b2a;";
+        let parser = permutation((character('a'), number(), literal("b")));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((('a', 2, "b"), ";"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn permutation_fails_when_a_parser_is_missing() {
+        let input = "ab;";
+        let parser = permutation((character('a'), character('b'), character('c')));
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingOneOfToParse(Found::Character(';')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn radix_digit_returns_the_value_of_the_digit() {
+        let input = "fF";
+        let parser = many(radix_digit(16));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((vec![15, 15], ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn radix_digit_names_the_radix() {
+        let input = "2";
+        let parser = radix_digit(2);
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("a base-2 digit".to_owned(), Found::Character('2')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn with_span_context_attaches_the_surrounding_text() {
+        let input = "let x = ?;";
+        let parser = with_span_context(seq!{
+            _keyword = literal("let ");
+            _name = any(char::is_alphabetic);
+            _equals = literal(" = ");
+            value = number();
+            => value
+        }, 8);
+
+        let actual = parser.parse(input);
+
+        let error = ParseError::ExpectingNamed("digit".to_owned(), Found::Character('?'));
+        let expected = Err(ParseError::InContext(Box::new(error), "x = ?;".to_owned()));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.unwrap_err().to_string(), "expected digit, found '?' in \"x = ?;\"");
     }
-}
 
-pub fn one_of<'a, T, P>(options: Vec<P>) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized {
-    OneOf::new(options)
-}
+    #[test]
+    fn with_span_context_finds_the_failure_after_an_earlier_match() {
+        let input = "let x = x;";
+        let parser = with_span_context(seq!{
+            _keyword = literal("let ");
+            _name = any(char::is_alphabetic);
+            _equals = literal(" = ");
+            value = number();
+            => value
+        }, 8);
 
-pub fn skip<'a, T, P>(parser: P) -> impl Parser<'a, ()> where T: 'a, P: Parser<'a, T> + Sized {
-    map(parser, |_|{()})
-}
+        let actual = parser.parse(input);
 
-pub fn space<'a>() -> impl Parser<'a, ()> {
-    skip(one_of(vec![character(' '), character('\t')]))
-}
+        let error = ParseError::ExpectingNamed("digit".to_owned(), Found::Character('x'));
+        let expected = Err(ParseError::InContext(Box::new(error), "x = x;".to_owned()));
+        assert_eq!(actual, expected);
+    }
 
-pub fn spaces<'a>() -> impl Parser<'a, ()> {
-    skip(many(space()))
-}
+    #[test]
+    fn with_span_context_follows_the_failure_into_the_input() {
+        let inputs = ["12 + 34 + x", "12 + 34 + "];
+        let parser = with_span_context(seq!{
+            a = number();
+            _plus = literal(" + ");
+            b = number();
+            _plus = literal(" + ");
+            c = number();
+            => a + b + c
+        }, 8);
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![
+            Err(ParseError::InContext(Box::new(ParseError::ExpectingNamed("digit".to_owned(), Found::Character('x'))), "4 + x".to_owned())),
+            Err(ParseError::InContext(Box::new(ParseError::ExpectingNamed("digit".to_owned(), Found::EndOfInput)), "4 + ".to_owned())),
+        ];
+        assert_eq!(actual, expected);
+    }
 
-pub fn newline<'a>() -> impl Parser<'a, ()> {
-    skip(one_of(vec![literal("\n"), literal("\r\n"), literal("\r")]))
-}
+    #[test]
+    fn contextual_keyword_matches_only_the_whole_identifier() {
+        let input = "selected from";
+        let identifier = || recognize(at_least(1, any(char::is_alphabetic)));
+        let parser = either(map(contextual_keyword(identifier(), "select"), |_| None), map(identifier(), Some));
 
-pub fn number<'a>() -> impl Parser<'a, u16> {
-    map(at_least(1, digit()), to_number)
-}
+        let actual = parser.parse(input);
 
-fn to_number(digits: Vec<char>) -> u16 {
-    let number: String = digits.into_iter().collect();
-    u16::from_str_radix(&number, 10).unwrap_or(0)
-}
+        let expected = Ok((Some("selected"), " from"));
+        assert_eq!(actual, expected);
+        assert_eq!(parser.parse("select *"), Ok((None, " *")));
+    }
 
-pub fn digit<'a>() -> impl Parser<'a, char> {
-    any(|c| c.is_ascii_digit())
-}
+    #[test]
+    fn cut_after_reports_the_error_after_the_prefix() {
+        let input = "let = 1";
+        let binding = seq!{
+            _space = character(' ');
+            name = any(char::is_alphabetic);
+            => name
+        };
+        let parser = either(cut_after(literal("let"), binding), any(char::is_alphabetic));
 
-pub fn end<'a, T, P>(parser: P) -> impl Parser<'a, T> where T: 'a, P: Parser<'a, T> + Sized {
-    move |input| {
-        parser.parse(input).and_then(|(result, rem)|{
-            if !rem.is_empty() {
-                return Err(ParseError::ExpectingToBeAtEndOfInput)
-            }
-            Ok((result, rem))
-        })
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::Committed(Box::new(ParseError::ExpectingPredicate(Found::Character('=')))));
+        assert_eq!(actual, expected);
+        assert_eq!(parser.parse("lex"), Ok(('l', "ex")));
     }
-}
 
+    #[test]
+    fn bracketed_list_allows_whitespace_and_a_trailing_separator() {
+        let parser = bracketed_list('[', number(), ',', ']');
 
-#[macro_export]
-macro_rules! sequence {
-    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
-        |input| {
-            let rem = input;
-            $(
-                let ($name, rem) = $parser.parse(rem)?;
-            )*
-            let result = $finish;
-            Ok((result, rem))
-        }
-    }};
-}
+        assert_eq!(parser.parse("[1, 2,\n 3,\n];"), Ok((vec![1, 2, 3], ";")));
+        assert_eq!(parser.parse("[ ]"), Ok((vec![], "")));
+        assert_eq!(parser.parse("[1]"), Ok((vec![1], "")));
+    }
 
-#[macro_export]
-macro_rules! move_sequence {
-    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
-        move |input| {
-            let rem = input;
-            $(
-                let ($name, rem) = $parser.parse(rem)?;
-            )*
-            let result = $finish;
-            Ok((result, rem))
-        }
-    }};
-}
+    #[test]
+    fn bracketed_list_reports_an_unclosed_bracket() {
+        let input = "[1, 2";
+        let parser = bracketed_list('[', number(), ',', ']');
 
+        let actual = parser.parse(input);
 
-#[macro_export]
-macro_rules! sequence_ignore_spaces {
-    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
-        |input| {
-            let rem = input;
-            $(
-                let (_, rem) = $crate::framework::spaces().parse(rem)?;
-                let ($name, rem) = $parser.parse(rem)?;
-            )*
-            let (_, rem) = $crate::framework::spaces().parse(rem)?;
-            let result = $finish;
-            Ok((result, rem))
-        }
-    }};
-}
+        let expected = Err(ParseError::ExpectingNamed("']' to close '['".to_owned(), Found::EndOfInput));
+        assert_eq!(actual, expected);
+        assert_eq!(parser.parse("[1 2]"), Err(ParseError::ExpectingNamed("']' to close '['".to_owned(), Found::Character('2'))));
+    }
 
-#[macro_export]
-macro_rules! move_sequence_ignore_spaces {
-    ($(let $name:ident = $parser:expr),+ => $finish:expr ) => {{
-        move |input| {
-            let rem = input;
-            $(
-                let (_, rem) = $crate::framework::spaces().parse(rem)?;
-                let ($name, rem) = $parser.parse(rem)?;
-            )*
-            let (_, rem) = $crate::framework::spaces().parse(rem)?;
-            let result = $finish;
-            Ok((result, rem))
-        }
-    }};
-}
+    #[test]
+    fn keywords_suggest_a_close_keyword() {
+        let input = "inclde \"x\"";
+        let parser = keywords(["define", "include", "import"]);
 
-pub fn blank_lines<'a>() -> impl Parser<'a, ()> {
-    skip(many(blank_line()))
-}
+        let actual = parser.parse(input);
 
-pub fn blank_line<'a>() -> impl Parser<'a, ()> {
-    sequence!{
-        let _spaces = spaces(),
-        let _newline = newline()
-        =>
-        ()
+        let expected = Err(ParseError::Misspelled("include".to_owned(), Found::Text("inclde".to_owned())));
+        assert_eq!(actual, expected);
+        assert_eq!(actual.unwrap_err().to_string(), "unexpected \"inclde\", did you mean \"include\"?");
+        assert_eq!(parser.parse("return"), Err(ParseError::ExpectingOneOfToParse(Found::Character('r'))));
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn parse_a_character() {
-        let input = "ABCD";
-        let parser = character('A');
+    fn parser_once_accepts_closures_that_move_their_captures() {
+        let input = "[1,2]";
+        let buffer = String::from("prefix:");
+        let parser = move |input: &'static str| {
+            let (digits, rem) = bracketed_list('[', digit(), ',', ']').parse(input)?;
+            let mut buffer = buffer;
+            buffer.extend(digits);
+            Ok((buffer, rem))
+        };
 
-        let actual = parser.parse(input);
+        let actual = map(at_most_once(parser), |text: String| text.len()).parse(input);
 
-        let expected = Ok(('A', "BCD"));
+        let expected = Ok((9, ""));
         assert_eq!(actual, expected);
+        assert_eq!(once(literal("a")).parse_once("ab"), Ok(("a", "b")));
     }
 
     #[test]
-    fn parse_any_digit() {
-        let input = "0123";
-        let parser = any(|c: char| c.is_ascii_digit());
+    fn at_most_once_panics_when_run_twice() {
+        let parser = at_most_once(move |input| literal("a").parse(input));
+        let _ = parser.parse("a");
+
+        let actual = testing::panics(|| { let _ = parser.parse("a"); });
+
+        assert!(actual);
+    }
+
+    #[test]
+    fn array_parses_exactly_n_items() {
+        let input = "ff:00:1a:rest";
+        let parser = array(seq!{
+            high = radix_digit(16);
+            low = radix_digit(16);
+            _colon = character(':');
+            => high * 16 + low
+        });
 
         let actual = parser.parse(input);
 
-        let expected = Ok(('0', "123"));
+        let expected = Ok(([255, 0, 26], "rest"));
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_any_digit_as_number() {
-        let input = "1230";
-        let parser = map(
-          any(|c: char| c.is_ascii_digit()), 
-          |c: char| c.to_digit(10).unwrap_or(0));
+    fn array_fails_with_fewer_than_n_items() {
+        let input = "12";
+        let parser = array::<_, _, 3>(digit());
 
         let actual = parser.parse(input);
 
-        let expected = Ok((1, "230"));
+        let expected = Err(ParseError::ExpectingNamed("digit".to_owned(), Found::EndOfInput));
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_literal_arrow() {
-        let input = "=> FF";
-        let parser = literal("=>");
+    fn retry_with_alternative_reports_the_dialect_that_parsed() {
+        let inputs = ["x = 1", "x: 1", "x 1"];
+        fn assignment<'a>(separator: &'static str) -> impl Parser<'a, (&'a str, u16)> {
+            separated_pair(take_while(|c| c.is_ascii_alphabetic()), literal(separator), number())
+        }
+        let parser = retry_with_alternative(assignment(" = "), assignment(": "));
 
-        let actual = parser.parse(input);
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
 
-        let expected = Ok(("=>", " FF"));
+        let expected = vec![
+            Ok(((Dialect::Primary, ("x", 1)), "")),
+            Ok(((Dialect::Alternative, ("x", 1)), "")),
+            Err(ParseError::ExpectingLiteral(" = ".to_owned(), Found::Text(" 1".to_owned()))),
+        ];
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_between_2_and_4_digits() {
-        let input = "12345";
-        let parser = between(2, 4, any(|c: char| c.is_ascii_digit()));
+    fn retry_with_alternative_needs_the_whole_input() {
+        let input = "1 2";
+        let parser = retry_with_alternative(recognize(number()), recognize(many(any(|_| true))));
 
         let actual = parser.parse(input);
 
-        let expected = Ok((vec!['1', '2', '3', '4'], "5"));
+        let expected = Ok(((Dialect::Alternative, "1 2"), ""));
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_one_of_a_or_b() {
-        let input = "a1";
-        let parser = one_of(vec![character('a'), character('b')]);
+    fn parse_within_spans_nodes_in_the_whole_source() {
+        let input = "size=\"12 kb\";";
+        let quoted = seq!{
+            _open = character('"');
+            body = take_while(|c| c != '"');
+            _close = character('"');
+            => body
+        };
+        let value = seq!{
+            amount = ast::node(input, number());
+            _space = character(' ');
+            unit = ast::node(input, literal("kb"));
+            => (amount, unit)
+        };
+        let parser = seq!{
+            _key = literal("size=");
+            value = parse_within(quoted, value);
+            => value
+        };
 
         let actual = parser.parse(input);
 
-        let expected = Ok(('a', "1"));
+        let expected = Ok(((ast::Node::new(12, ast::Span::new(6, 8)), ast::Node::new("kb", ast::Span::new(9, 11))), ";"));
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_skip_many_spaces() {
-        let input = "           next";
-        let parser = skip(many(character(' ')));
+    fn parse_within_needs_the_whole_slice() {
+        let input = "(1 2) 3";
+        let parser = parse_within(seq!{
+            _open = character('(');
+            body = take_while(|c| c != ')');
+            _close = character(')');
+            => body
+        }, number());
 
         let actual = parser.parse(input);
 
-        let expected = Ok(((), "next"));
+        let expected = Err(ParseError::ExpectingToBeAtEndOfInput(Found::Text(" 2".to_owned())));
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_spaces() {
-        let input = "          \tnext";
-        let parser = spaces();
+    fn parse_within_reports_what_follows_the_slice() {
+        let input = "size=\"12 \";";
+        let quoted = seq!{
+            _open = character('"');
+            body = take_while(|c| c != '"');
+            _close = character('"');
+            => body
+        };
+        let value = seq!{
+            amount = number();
+            _space = character(' ');
+            _unit = literal("kb");
+            => amount
+        };
+        let parser = seq!{
+            _key = literal("size=");
+            value = parse_within(quoted, value);
+            => value
+        };
 
         let actual = parser.parse(input);
 
-        let expected = Ok(((), "next"));
+        let expected = Err(ParseError::ExpectingLiteral("kb".to_owned(), Found::Character('"')));
         assert_eq!(actual, expected);
     }
 
     #[test]
-    fn parse_newline() {
-        let input = "\n";
-        let parser = newline();
+    fn eol_or_eof_should_not_accept_other_input() {
+        let input = "next";
+        let parser = eol_or_eof();
 
         let actual = parser.parse(input);
 
-        let expected = Ok(((), ""));
+        let expected = Err(ParseError::ExpectingOneOfToParse(Found::Character('n')));
         assert_eq!(actual, expected);
     }
 
@@ -467,10 +3044,140 @@ mod tests {
 
         let actual = parser.parse(input);
 
-        let expected = Err(ParseError::ExpectingToBeAtEndOfInput);
+        let expected = Err(ParseError::ExpectingToBeAtEndOfInput(Found::Text(" ".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+
+    #[test]
+    fn parse_not_followed_by() {
+        let input = "10 px";
+        let parser = not_followed_by(number(), character('%'));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((10, " px"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn not_followed_by_should_fail_when_follower_matches() {
+        let input = "10%";
+        let parser = not_followed_by(number(), character('%'));
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::UnexpectedFollowingInput(Found::Text("%".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn keyword_should_not_match_prefix_of_identifier() {
+        let parser = not_followed_by(literal("let"), any(|c: char| c.is_alphanumeric()));
+
+        assert_eq!(parser.parse("let x"), Ok(("let", " x")));
+        assert_eq!(parser.parse("letter"), Err(ParseError::UnexpectedFollowingInput(Found::Text("ter".to_owned()))));
+    }
+
+    #[test]
+    fn parse_followed_by_without_consuming_follower() {
+        let input = "10px";
+        let parser = followed_by(number(), literal("px"));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((10, "px"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn followed_by_should_fail_when_follower_fails() {
+        let input = "10em";
+        let parser = followed_by(number(), literal("px"));
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingLiteral("px".to_owned(), Found::Text("em".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn word_boundary_should_not_consume_input() {
+        let parser = sequence!{
+            let keyword = literal("let"),
+            let _boundary = word_boundary()
+            =>
+            keyword
+        };
+
+        assert_eq!(parser.parse("let(x)"), Ok(("let", "(x)")));
+        assert_eq!(parser.parse("let_x"), Err(ParseError::ExpectingWordBoundary(Found::Character('_'))));
+    }
+
+    #[test]
+    fn parse_escaped_string_content() {
+        let input = r#"say \"hi\"" rest"#;
+        let parser = escaped(
+            take_till(|c: char| c == '"' || c == '\\'),
+            '\\',
+            one_of(vec![character('"'), character('\\')]));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((r#"say \"hi\""#, r#"" rest"#));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn escaped_should_fail_on_unknown_escape() {
+        let input = r#"bad \q""#;
+        let parser = escaped(
+            take_till(|c: char| c == '"' || c == '\\'),
+            '\\',
+            one_of(vec![character('"'), character('\\')]));
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingOneOfToParse(Found::Character('q')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_escaped_transform_percent_encoding() {
+        let input = "two%20words&";
+        let parser = escaped_transform(
+            take_till(|c: char| c == '%' || c == '&'),
+            '%',
+            map(between(2, 2, any(|c: char| c.is_ascii_hexdigit())), |digits: Vec<char>| {
+                let hex: String = digits.into_iter().collect();
+                u8::from_str_radix(&hex, 16).unwrap_or(0) as char
+            }));
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("two words".to_owned(), "&"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_records_line_by_line() {
+        let input = "12\nab\r\n34\n";
+
+        let actual: Vec<Result<u16, ParseError>> = parse_records(number(), input).collect();
+
+        let expected = vec![Ok(12), Err(ParseError::ExpectingNamed("digit".to_owned(), Found::Character('a'))), Ok(34)];
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn parse_records_rejects_trailing_input_in_a_record() {
+        let input = "12 \n34";
+
+        let actual: Vec<Result<u16, ParseError>> = parse_records(number(), input).collect();
+
+        let expected = vec![Err(ParseError::ExpectingToBeAtEndOfInput(Found::Text(" ".to_owned()))), Ok(34)];
+        assert_eq!(actual, expected);
+    }
 
     #[test]
     fn parse_a_sequence_of_parsers() {
@@ -502,6 +3209,23 @@ mod tests {
         assert!(rem.is_empty());
     }
 
+    #[test]
+    fn parse_a_sequence_of_parsers_skipping_junk() {
+        let parser = sequence_skipping!{
+            skip(many(satisfy_named("whitespace", char::is_whitespace)));
+            let key = take_while(|c: char| c.is_alphabetic()),
+            let _equal = character('='),
+            let value = number()
+            =>
+            (key, value)
+        };
+
+        let (result, rem) = parser.parse("\n  step\n\t=  8 \n").expect("failed to parse");
+
+        assert_eq!(("step", 8), result);
+        assert!(rem.is_empty());
+    }
+
     #[test]
     fn parse_blank_lines() {
         let input = "\n \n\t\n \t \n";
@@ -0,0 +1,70 @@
+use bumpalo::Bump;
+use bumpalo::collections::Vec as BumpVec;
+
+use super::{Parser, ParseError};
+
+// Outputs live as long as the arena rather than being owned, so a whole tree
+// can be dropped at once by dropping the arena.
+pub fn alloc<'a, 'b, T, P>(arena: &'b Bump, parser: P) -> impl Parser<'a, &'b T> where T: 'a + 'b, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        let (result, rem) = parser.parse(input)?;
+        Ok((&*arena.alloc(result), rem))
+    }
+}
+
+pub fn alloc_str<'a, 'b, P>(arena: &'b Bump, parser: P) -> impl Parser<'a, &'b str> where P: Parser<'a, &'a str> + Sized {
+    move |input: &'a str| {
+        let (text, rem) = parser.parse(input)?;
+        Ok((&*arena.alloc_str(text), rem))
+    }
+}
+
+pub fn many_in<'a, 'b, T, P>(arena: &'b Bump, parser: P) -> impl Parser<'a, BumpVec<'b, T>> where T: 'a + 'b, P: Parser<'a, T> + Sized {
+    move |input: &'a str| {
+        let mut result = BumpVec::new_in(arena);
+        let mut rem = input;
+        loop {
+            match parser.parse(rem) {
+                Ok((_, rest)) if rest.len() == rem.len() => return Err(ParseError::InfiniteLoopDetected),
+                Ok((value, rest)) => {
+                    result.push(value);
+                    rem = rest;
+                }
+                Err(e) if e.is_fatal() => return Err(e),
+                Err(_) => return Ok((result, rem)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{any, at_least, recognize, spaces};
+
+    #[derive(Debug, PartialEq)]
+    struct Word<'b>(&'b str);
+
+    fn word<'a, 'b>(arena: &'b Bump) -> impl Parser<'a, Word<'b>> {
+        let text = alloc_str(arena, recognize(at_least(1, any(char::is_alphabetic))));
+        move |input: &'a str| {
+            let (text, rem) = text.parse(input)?;
+            let (_, rem) = spaces().parse(rem)?;
+            Ok((Word(text), rem))
+        }
+    }
+
+    #[test]
+    fn arena_outputs_outlive_the_input() {
+        let arena = Bump::new();
+        let words = {
+            let input = String::from("one two");
+            let parser = many_in(&arena, alloc(&arena, word(&arena)));
+            let (words, _) = parser.parse(&input).expect("to parse words");
+            words
+        };
+
+        assert_eq!(words.iter().map(|word| word.0).collect::<Vec<_>>(), vec!["one", "two"]);
+        assert_eq!(*words[1], Word("two"));
+    }
+}
@@ -0,0 +1,318 @@
+use super::{Parser, ParseError, ParseResult};
+
+// Where something is in the input. Text is measured in byte offsets, which
+// is what parsers over `&str` report; input that has already been split into
+// tokens is measured in `TokenIndex`es instead.
+pub trait Position: Copy + Ord {
+    fn distance(start: Self, end: Self) -> usize;
+}
+
+impl Position for usize {
+    fn distance(start: Self, end: Self) -> usize {
+        end - start
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TokenIndex(pub usize);
+
+impl Position for TokenIndex {
+    fn distance(start: Self, end: Self) -> usize {
+        end.0 - start.0
+    }
+}
+
+// Spans order by where they start, then by where they end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Span<P = usize> {
+    pub start: P,
+    pub end: P,
+}
+
+impl<P: Position> Span<P> {
+    pub fn new(start: P, end: P) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        P::distance(self.start, self.end)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn merge(self, other: Self) -> Self {
+        Span::new(self.start.min(other.start), self.end.max(other.end))
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    // Overlapping or directly next to each other, with nothing in between.
+    pub fn touches(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+impl Span {
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        &source[self.start..self.end]
+    }
+
+    // The tokens that overlap this span of bytes, the inverse of `to_bytes`.
+    pub fn to_tokens(&self, tokens: &[Span]) -> Span<TokenIndex> {
+        let start = tokens.iter().position(|token| token.end > self.start).unwrap_or(tokens.len());
+        let end = tokens.iter().rposition(|token| token.start < self.end).map_or(start, |index| index + 1).max(start);
+        Span::new(TokenIndex(start), TokenIndex(end))
+    }
+}
+
+impl Span<TokenIndex> {
+    // The bytes from the start of the first token to the end of the last,
+    // given the byte span of every token. An empty span sits at the start of
+    // the token it points at, or at the end of the input after the last one.
+    pub fn to_bytes(&self, tokens: &[Span]) -> Option<Span> {
+        let offset = |index: usize| match tokens.get(index) {
+            Some(token) => Some(token.start),
+            None if index == tokens.len() => Some(tokens.last().map_or(0, |token| token.end)),
+            None => None,
+        };
+        if self.is_empty() {
+            return offset(self.start.0).map(|start| Span::new(start, start));
+        }
+        let start = tokens.get(self.start.0)?.start;
+        let end = tokens.get(self.end.0 - 1)?.end;
+        Some(Span::new(start, end))
+    }
+}
+
+// Sorted, with spans that touch merged into one.
+pub fn merge_adjacent<P: Position>(spans: impl IntoIterator<Item = Span<P>>) -> Vec<Span<P>> {
+    let mut spans: Vec<Span<P>> = spans.into_iter().collect();
+    spans.sort();
+    let mut merged: Vec<Span<P>> = vec![];
+    for span in spans {
+        match merged.last_mut() {
+            Some(last) if last.touches(&span) => *last = last.merge(span),
+            _ => merged.push(span),
+        }
+    }
+    merged
+}
+
+// The node that got furthest into the input comes first, e.g. to report the
+// error from the most promising alternative. Ties keep a fixed order by
+// start and then by their original order, so output is the same every run.
+pub fn furthest_first<T, P: Position>(nodes: &mut [Node<T, P>]) {
+    nodes.sort_by(|a, b| b.span.end.cmp(&a.span.end).then(a.span.start.cmp(&b.span.start)));
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node<T, P = usize> {
+    pub value: T,
+    pub span: Span<P>,
+}
+
+impl<T, P: Position> Node<T, P> {
+    pub fn new(value: T, span: Span<P>) -> Self {
+        Self { value, span }
+    }
+
+    // A parent node covering all of its children, for building nodes after
+    // the fact rather than by wrapping the parser that produced them.
+    pub fn spanning<'n, C: 'n>(value: T, children: impl IntoIterator<Item = &'n Node<C, P>>) -> Option<Self> where P: 'n {
+        enclosing(children).map(|span| Self::new(value, span))
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Node<U, P> {
+        Node::new(f(self.value), self.span)
+    }
+
+    pub fn as_ref(&self) -> Node<&T, P> {
+        Node::new(&self.value, self.span)
+    }
+}
+
+pub fn enclosing<'n, T: 'n, P: Position + 'n>(nodes: impl IntoIterator<Item = &'n Node<T, P>>) -> Option<Span<P>> {
+    nodes.into_iter().map(|node| node.span).reduce(Span::merge)
+}
+
+// The input is found in the source by where it lies in memory rather than by
+// how much is left, so spans stay in terms of the whole source even when a
+// parser is run on a slice out of the middle of it, as with `parse_within`.
+pub struct Spanned<'s, P> {
+    source: &'s str,
+    parser: P,
+}
+
+impl<'a, 's, T, P> Parser<'a, Node<T>> for Spanned<'s, P> where P: Parser<'a, T> + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Node<T>> {
+        let (value, rem) = self.parser.parse(input)?;
+        let start = offset_in(self.source, input).ok_or(ParseError::NotInSource)?;
+        let span = Span::new(start, start + input.len() - rem.len());
+        Ok((Node::new(value, span), rem))
+    }
+}
+
+// Where `input` starts in `source`, as long as it lies entirely within it.
+fn offset_in(source: &str, input: &str) -> Option<usize> {
+    let start = (input.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+    match start.checked_add(input.len()) {
+        Some(end) if end <= source.len() => Some(start),
+        _ => None,
+    }
+}
+
+impl<'s, P> Spanned<'s, P> {
+    pub fn new(source: &'s str, parser: P) -> Self {
+        Self { source, parser }
+    }
+}
+
+pub fn node<'a, 's, T, P>(source: &'s str, parser: P) -> impl Parser<'a, Node<T>> + 's where T: 'a, P: Parser<'a, T> + Sized + 's {
+    Spanned::new(source, parser)
+}
+
+pub fn map_with_span<'a, 's, T, U, P, F>(source: &'s str, parser: P, map: F) -> impl Parser<'a, U> + 's where T: 'a, P: Parser<'a, T> + Sized + 's, F: Fn(T, Span) -> U + 's {
+    let spanned = Spanned::new(source, parser);
+    move |input: &'a str| {
+        let (node, rem) = spanned.parse(input)?;
+        Ok((map(node.value, node.span), rem))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{character, digit, number};
+
+    #[test]
+    fn token_spans_convert_to_and_from_byte_spans() {
+        let tokens = [Span::new(0, 3), Span::new(4, 5), Span::new(6, 7), Span::new(8, 10)];
+        let input = Span::new(TokenIndex(1), TokenIndex(3));
+
+        let actual = input.to_bytes(&tokens);
+
+        let expected = Some(Span::new(4, 7));
+        assert_eq!(actual, expected);
+        assert_eq!(Span::new(4, 7).to_tokens(&tokens), input);
+        assert_eq!(input.len(), 2);
+    }
+
+    #[test]
+    fn empty_token_spans_sit_between_tokens() {
+        let tokens = [Span::new(0, 3), Span::new(4, 5)];
+        let inputs = [Span::new(TokenIndex(1), TokenIndex(1)), Span::new(TokenIndex(2), TokenIndex(2)), Span::new(TokenIndex(2), TokenIndex(3))];
+
+        let actual: Vec<_> = inputs.iter().map(|span| span.to_bytes(&tokens)).collect();
+
+        let expected = vec![Some(Span::new(4, 4)), Some(Span::new(5, 5)), None];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn node_rejects_input_from_elsewhere() {
+        let source = "a12b";
+        let input = String::from("12b");
+        let parser = node(source, number());
+
+        let actual = parser.parse(&input);
+
+        let expected = Err(ParseError::NotInSource);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn node_records_the_span_it_consumed() {
+        let input = "a12b";
+        let parser = node(input, number());
+
+        let actual = parser.parse(&input[1..]);
+
+        let expected = Ok((Node::new(12, Span::new(1, 3)), "b"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn nested_nodes_share_one_source() {
+        let input = "(7)";
+        let parser = node(input, crate::sequence!{
+            let _open = character('('),
+            let value = node(input, digit()),
+            let _close = character(')')
+            => value
+        });
+
+        let (outer, _) = parser.parse(input).expect("to parse a group");
+
+        assert_eq!(outer.span, Span::new(0, 3));
+        assert_eq!(outer.value, Node::new('7', Span::new(1, 2)));
+        assert_eq!(outer.value.span.text(input), "7");
+    }
+
+    #[test]
+    fn map_with_span_passes_the_span_along() {
+        let input = "x = 42;";
+        let parser = map_with_span(input, number(), |value, span| (value, span.start, span.text(input)));
+
+        let actual = parser.parse(&input[4..]);
+
+        let expected = Ok(((42, 4, "42"), ";"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn spanning_merges_child_spans() {
+        let children = [Node::new('a', Span::new(4, 6)), Node::new('b', Span::new(1, 2))];
+
+        let actual = Node::spanning("parent", &children);
+
+        let expected = Some(Node::new("parent", Span::new(1, 6)));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn spans_overlap_only_when_they_share_text() {
+        let span = Span::new(2, 5);
+
+        assert!(span.overlaps(&Span::new(4, 8)));
+        assert!(!span.overlaps(&Span::new(5, 8)));
+        assert!(span.touches(&Span::new(5, 8)));
+        assert!(!span.touches(&Span::new(6, 8)));
+    }
+
+    #[test]
+    fn merge_adjacent_joins_touching_spans() {
+        let spans = vec![Span::new(8, 9), Span::new(3, 5), Span::new(0, 3), Span::new(4, 6)];
+
+        let actual = merge_adjacent(spans);
+
+        let expected = vec![Span::new(0, 6), Span::new(8, 9)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn furthest_first_sorts_by_end_then_start() {
+        let mut errors = vec![
+            Node::new(ParseError::EndOfInput, Span::new(0, 2)),
+            Node::new(ParseError::GenericError, Span::new(3, 7)),
+            Node::new(ParseError::InfiniteLoopDetected, Span::new(1, 7)),
+        ];
+
+        furthest_first(&mut errors);
+
+        let values: Vec<_> = errors.into_iter().map(|node| node.value).collect();
+        assert_eq!(values, vec![ParseError::InfiniteLoopDetected, ParseError::GenericError, ParseError::EndOfInput]);
+    }
+
+    #[test]
+    fn spanning_nothing_has_no_span() {
+        let children: [Node<char>; 0] = [];
+
+        let actual = Node::spanning("parent", &children);
+
+        assert_eq!(actual, None);
+    }
+}
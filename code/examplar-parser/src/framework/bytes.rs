@@ -0,0 +1,300 @@
+use super::{Found, ParseError};
+
+// `Parser` works over text, so binary input gets plain functions and closures
+// of its own that follow the same shape: the value and whatever is left.
+pub type ByteResult<'a, T> = Result<(T, &'a [u8]), ParseError>;
+
+// Printable ASCII is shown as the character it is, any other byte in hex.
+pub fn found(input: &[u8]) -> Found {
+    match input.first() {
+        Some(&byte) if byte == b' ' || byte.is_ascii_graphic() => Found::Character(byte as char),
+        Some(byte) => Found::Text(format!("{:#04x}", byte)),
+        None => Found::EndOfInput,
+    }
+}
+
+pub fn take<'a>(count: usize) -> impl Fn(&'a [u8]) -> ByteResult<'a, &'a [u8]> {
+    move |input: &'a [u8]| {
+        if input.len() < count {
+            return Err(ParseError::Incomplete);
+        }
+        Ok((&input[..count], &input[count..]))
+    }
+}
+
+pub fn u8(input: &[u8]) -> ByteResult<'_, u8> {
+    let (bytes, rem) = take(1)(input)?;
+    Ok((bytes[0], rem))
+}
+
+pub fn byte<'a>(expected: u8) -> impl Fn(&'a [u8]) -> ByteResult<'a, u8> {
+    move |input: &'a [u8]| {
+        match u8(input)? {
+            (byte, rem) if byte == expected => Ok((byte, rem)),
+            _ => Err(ParseError::ExpectingCharacter(expected as char, found(input))),
+        }
+    }
+}
+
+// Input that stops part way through a match is `Incomplete`, not a mismatch.
+pub fn tag<'a, 't>(expected: &'t [u8]) -> impl Fn(&'a [u8]) -> ByteResult<'a, &'a [u8]> + 't {
+    move |input: &'a [u8]| {
+        let length = expected.len().min(input.len());
+        if input[..length] != expected[..length] {
+            return Err(ParseError::ExpectingLiteral(String::from_utf8_lossy(expected).into_owned(), found(input)));
+        }
+        take(expected.len())(input)
+    }
+}
+
+// Zero or more matching bytes. These have to be followed by something else,
+// since more input could carry on the run.
+pub fn take_while<'a, P>(predicate: P) -> impl Fn(&'a [u8]) -> ByteResult<'a, &'a [u8]> where P: Fn(u8) -> bool {
+    move |input: &'a [u8]| {
+        match input.iter().position(|byte| !predicate(*byte)) {
+            Some(length) => Ok((&input[..length], &input[length..])),
+            None => Err(ParseError::Incomplete),
+        }
+    }
+}
+
+pub fn take_while1<'a, 'n, P>(name: &'n str, predicate: P) -> impl Fn(&'a [u8]) -> ByteResult<'a, &'a [u8]> + 'n where P: Fn(u8) -> bool + 'n {
+    move |input: &'a [u8]| {
+        match take_while(&predicate)(input)? {
+            ([], _) => Err(ParseError::ExpectingNamed(name.to_owned(), found(input))),
+            taken => Ok(taken),
+        }
+    }
+}
+
+pub fn be_u16(input: &[u8]) -> ByteResult<'_, u16> {
+    let (bytes, rem) = take(2)(input)?;
+    Ok((u16::from_be_bytes([bytes[0], bytes[1]]), rem))
+}
+
+pub fn be_u32(input: &[u8]) -> ByteResult<'_, u32> {
+    let (bytes, rem) = take(4)(input)?;
+    Ok((u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]), rem))
+}
+
+// Little-endian base 128: seven bits a byte, lowest first, with the high bit
+// set on every byte but the last. Running out of input before the last byte
+// is `Incomplete`; bits past the 64th are an overflow.
+pub fn varint(input: &[u8]) -> ByteResult<'_, u64> {
+    let mut value = 0u64;
+    for (i, byte) in input.iter().enumerate() {
+        let bits = u64::from(byte & 0x7f);
+        let shift = 7 * i as u32;
+        if shift >= 64 || (bits << shift) >> shift != bits {
+            return Err(ParseError::Overflow(found(input)));
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Ok((value, &input[i + 1..]));
+        }
+    }
+    Err(ParseError::Incomplete)
+}
+
+// Signed values are zig-zag encoded first, 0, -1, 1, -2, ... becoming
+// 0, 1, 2, 3, ..., so small negative numbers stay short.
+pub fn zigzag(input: &[u8]) -> ByteResult<'_, i64> {
+    let (value, rem) = varint(input)?;
+    Ok(((value >> 1) as i64 ^ -((value & 1) as i64), rem))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Element<'a, K, T> {
+    Known(K, T),
+    Unknown(K, &'a [u8]),
+}
+
+type Payload<'a, T> = Box<dyn Fn(&'a [u8]) -> ByteResult<'a, T> + 'a>;
+
+// Reads a tag, then a length, then hands exactly that many bytes to the
+// payload parser registered for the tag, which has to use all of them.
+// Values with tags nobody registered are kept as raw bytes unless
+// `reject_unknown` is set.
+pub struct Tlv<'a, K, T, G, L> {
+    tag: G,
+    length: L,
+    payloads: Vec<(K, Payload<'a, T>)>,
+    reject_unknown: bool,
+}
+
+impl<'a, K, T, G, L> Tlv<'a, K, T, G, L> where K: PartialEq, G: Fn(&'a [u8]) -> ByteResult<'a, K>, L: Fn(&'a [u8]) -> ByteResult<'a, usize> {
+    pub fn new(tag: G, length: L) -> Self {
+        Self { tag, length, payloads: vec![], reject_unknown: false }
+    }
+
+    pub fn payload<P>(mut self, tag: K, parser: P) -> Self where P: Fn(&'a [u8]) -> ByteResult<'a, T> + 'a {
+        self.payloads.push((tag, Box::new(parser)));
+        self
+    }
+
+    pub fn reject_unknown(mut self) -> Self {
+        self.reject_unknown = true;
+        self
+    }
+
+    pub fn parse(&self, input: &'a [u8]) -> ByteResult<'a, Element<'a, K, T>> {
+        let (tag, rem) = (self.tag)(input)?;
+        let (length, rem) = (self.length)(rem)?;
+        let (value, rem) = take(length)(rem)?;
+        match self.payloads.iter().find(|(known, _)| *known == tag) {
+            Some((_, payload)) => match payload(value)? {
+                (result, []) => Ok((Element::Known(tag, result), rem)),
+                (_, rest) => Err(ParseError::UnexpectedFollowingInput(found(rest))),
+            },
+            None if self.reject_unknown => Err(ParseError::ExpectingNamed("a known tag".to_owned(), found(input))),
+            None => Ok((Element::Unknown(tag, value), rem)),
+        }
+    }
+
+    pub fn parse_all(&self, input: &'a [u8]) -> Result<Vec<Element<'a, K, T>>, ParseError> {
+        let mut elements = vec![];
+        let mut rem = input;
+        while !rem.is_empty() {
+            let (element, rest) = self.parse(rem)?;
+            elements.push(element);
+            rem = rest;
+        }
+        Ok(elements)
+    }
+}
+
+pub fn tlv<'a, K, T, G, L>(tag: G, length: L) -> Tlv<'a, K, T, G, L> where K: PartialEq, G: Fn(&'a [u8]) -> ByteResult<'a, K>, L: Fn(&'a [u8]) -> ByteResult<'a, usize> {
+    Tlv::new(tag, length)
+}
+
+// Parses the next `length` bytes with `payload`, which has to use all of them,
+// and only accepts the value if `check` holds for those exact bytes, as with a
+// CRC over a frame.
+pub fn validated_region<'a, T, P, C>(length: usize, payload: P, check: C) -> impl Fn(&'a [u8]) -> ByteResult<'a, T> where P: Fn(&'a [u8]) -> ByteResult<'a, T>, C: Fn(&[u8]) -> bool {
+    move |input: &'a [u8]| {
+        let (region, rem) = take(length)(input)?;
+        let (result, rest) = payload(region)?;
+        if !rest.is_empty() {
+            return Err(ParseError::UnexpectedFollowingInput(found(rest)));
+        }
+        if !check(region) {
+            return Err(ParseError::ChecksumMismatch(length));
+        }
+        Ok((result, rem))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn length(input: &[u8]) -> ByteResult<'_, usize> {
+        u8(input).map(|(length, rem)| (length as usize, rem))
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Field {
+        Id(u16),
+        Name(String),
+    }
+
+    #[test]
+    fn varint_reads_seven_bits_a_byte() {
+        let inputs: [&[u8]; 3] = [&[0x01, 0xff], &[0xac, 0x02], &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]];
+        let parser = varint;
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input)).collect();
+
+        let expected = vec![Ok((1, &[0xff][..])), Ok((300, &[][..])), Ok((u64::MAX, &[][..]))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn varint_rejects_truncated_and_overlong_input() {
+        let inputs: [&[u8]; 2] = [&[0x80, 0x80], &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]];
+        let parser = varint;
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input)).collect();
+
+        let expected = vec![Err(ParseError::Incomplete), Err(ParseError::Overflow(Found::Text("0xff".to_owned())))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tag_tells_a_mismatch_from_a_truncation() {
+        let inputs: [&[u8]; 3] = [b"HTTP/1.1", b"HTT", b"HTX"];
+        let parser = tag(b"HTTP/");
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input)).collect();
+
+        let expected = vec![Ok((&b"HTTP/"[..], &b"1.1"[..])), Err(ParseError::Incomplete), Err(ParseError::ExpectingLiteral("HTTP/".to_owned(), Found::Character('H')))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn take_while1_needs_a_match_and_an_end() {
+        let inputs: [&[u8]; 3] = [b"123;", b";", b"123"];
+        let parser = take_while1("a digit", |byte: u8| byte.is_ascii_digit());
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input)).collect();
+
+        let expected = vec![Ok((&b"123"[..], &b";"[..])), Err(ParseError::ExpectingNamed("a digit".to_owned(), Found::Character(';'))), Err(ParseError::Incomplete)];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn zigzag_alternates_signs() {
+        let inputs: [&[u8]; 5] = [&[0], &[1], &[2], &[3], &[0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]];
+        let parser = zigzag;
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input).map(|(value, _)| value)).collect();
+
+        let expected = vec![Ok(0), Ok(-1), Ok(1), Ok(-2), Ok(i64::MAX)];
+        assert_eq!(actual, expected);
+    }
+
+    fn sums_to_zero(bytes: &[u8]) -> bool {
+        bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte)) == 0
+    }
+
+    #[test]
+    fn validated_region_checks_the_bytes_it_parsed() {
+        let inputs: [&[u8]; 2] = [&[0x01, 0x02, 0xfd, 0xaa], &[0x01, 0x02, 0xfe, 0xaa]];
+        let parser = validated_region(3, |input| be_u16(input).and_then(|(value, rem)| u8(rem).map(|(_, rem)| (value, rem))), sums_to_zero);
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input)).collect();
+
+        let expected = vec![Ok((0x0102, &[0xaa][..])), Err(ParseError::ChecksumMismatch(3))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tlv_dispatches_on_the_tag_and_skips_unknown_ones() {
+        let input = [1, 2, 0x01, 0x02, 9, 1, 0xff, 2, 2, b'h', b'i'];
+        let parser = tlv(u8, length)
+            .payload(1, |input| be_u16(input).map(|(id, rem)| (Field::Id(id), rem)))
+            .payload(2, |input| Ok((Field::Name(String::from_utf8_lossy(input).into_owned()), &input[input.len()..])));
+
+        let actual = parser.parse_all(&input);
+
+        let expected = Ok(vec![
+            Element::Known(1, Field::Id(0x0102)),
+            Element::Unknown(9, &[0xff][..]),
+            Element::Known(2, Field::Name("hi".to_owned())),
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn tlv_rejects_payloads_that_leave_bytes_over() {
+        let input = [1, 3, 0x01, 0x02, 0x03];
+        let parser = tlv(u8, length).payload(1, be_u16);
+
+        let actual = parser.parse(&input);
+
+        let expected = Err(ParseError::UnexpectedFollowingInput(Found::Text("0x03".to_owned())));
+        assert_eq!(actual, expected);
+        assert_eq!(parser.parse(&[1, 3, 0x01]), Err(ParseError::Incomplete));
+        assert_eq!(tlv(u8, length).payload(1, be_u16).reject_unknown().parse(&[7, 0]), Err(ParseError::ExpectingNamed("a known tag".to_owned(), Found::Text("0x07".to_owned()))));
+    }
+}
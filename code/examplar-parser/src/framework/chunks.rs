@@ -0,0 +1,152 @@
+use std::borrow::Cow;
+
+use super::{Found, ParseError};
+
+// Text held in several pieces, like the leaves of a rope or a chain of network
+// buffers. `Parser` needs the input as one `&str`, so text like this is read
+// with the functions here, which follow the same shape: the value and a cursor
+// at whatever is left. Nothing is joined up front; only a value that itself
+// runs over a boundary between chunks is copied.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cursor<'c, 'a> {
+    chunks: &'c [&'a str],
+    chunk: usize,
+    offset: usize,
+}
+
+pub type ChunkResult<'c, 'a, T> = Result<(T, Cursor<'c, 'a>), ParseError>;
+
+impl<'c, 'a> Cursor<'c, 'a> {
+    pub fn new(chunks: &'c [&'a str]) -> Self {
+        Self { chunks, chunk: 0, offset: 0 }.skip_finished_chunks()
+    }
+
+    // Moves past the end of the chunk and any empty ones after it, so the
+    // cursor is in front of a character unless all of the input has been read.
+    fn skip_finished_chunks(mut self) -> Self {
+        while self.chunk < self.chunks.len() && self.offset == self.chunks[self.chunk].len() {
+            self.chunk += 1;
+            self.offset = 0;
+        }
+        self
+    }
+
+    fn advance(self, bytes: usize) -> Self {
+        Self { offset: self.offset + bytes, ..self }.skip_finished_chunks()
+    }
+
+    // What is left of the chunk the cursor is in.
+    pub fn rest_of_chunk(&self) -> &'a str {
+        self.chunks.get(self.chunk).map_or("", |chunk| &chunk[self.offset..])
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.chunk == self.chunks.len()
+    }
+
+    // In bytes from the start of the first chunk.
+    pub fn position(&self) -> usize {
+        self.chunks[..self.chunk].iter().map(|chunk| chunk.len()).sum::<usize>() + self.offset
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest_of_chunk().chars().next()
+    }
+
+    fn found(&self) -> Found {
+        Found::at(self.rest_of_chunk())
+    }
+}
+
+pub fn character<'c, 'a>(expected: char) -> impl Fn(Cursor<'c, 'a>) -> ChunkResult<'c, 'a, char> where 'a: 'c {
+    move |cursor: Cursor<'c, 'a>| {
+        match cursor.peek() {
+            Some(c) if c == expected => Ok((c, cursor.advance(c.len_utf8()))),
+            _ => Err(ParseError::ExpectingCharacter(expected, cursor.found())),
+        }
+    }
+}
+
+pub fn literal<'c, 'a, 'l>(expected: &'l str) -> impl Fn(Cursor<'c, 'a>) -> ChunkResult<'c, 'a, ()> + 'l where 'a: 'c {
+    move |start: Cursor<'c, 'a>| {
+        let mut cursor = start;
+        for c in expected.chars() {
+            match cursor.peek() {
+                Some(next) if next == c => cursor = cursor.advance(c.len_utf8()),
+                _ => return Err(ParseError::ExpectingLiteral(expected.to_owned(), start.found())),
+            }
+        }
+        Ok(((), cursor))
+    }
+}
+
+// Borrows the run of matching characters when it lies within one chunk, and
+// only copies it when it continues into the next.
+pub fn take_while<'c, 'a, F>(predicate: F) -> impl Fn(Cursor<'c, 'a>) -> ChunkResult<'c, 'a, Cow<'a, str>> where 'a: 'c, F: Fn(char) -> bool {
+    move |start: Cursor<'c, 'a>| {
+        let mut text = Cow::Borrowed("");
+        let mut cursor = start;
+        while !cursor.is_at_end() {
+            let chunk = cursor.rest_of_chunk();
+            let length = chunk.find(|c| !predicate(c)).unwrap_or(chunk.len());
+            if text.is_empty() {
+                text = Cow::Borrowed(&chunk[..length]);
+            } else if length > 0 {
+                text.to_mut().push_str(&chunk[..length]);
+            }
+            cursor = cursor.advance(length);
+            if length < chunk.len() {
+                break;
+            }
+        }
+        Ok((text, cursor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting<'c, 'a>(cursor: Cursor<'c, 'a>) -> ChunkResult<'c, 'a, (Cow<'a, str>, Cow<'a, str>)> {
+        let word = take_while(char::is_alphanumeric);
+        let (key, cursor) = word(cursor)?;
+        let (_, cursor) = literal(" = ")(cursor)?;
+        let (value, cursor) = word(cursor)?;
+        let (_, cursor) = character(';')(cursor)?;
+        Ok(((key, value), cursor))
+    }
+
+    #[test]
+    fn take_while_borrows_text_within_a_chunk() {
+        let input = ["key = value;", "rest"];
+        let parser = setting;
+
+        let (actual, rem) = parser(Cursor::new(&input)).expect("to parse a setting");
+
+        assert!(matches!(actual, (Cow::Borrowed("key"), Cow::Borrowed("value"))));
+        assert_eq!((rem.rest_of_chunk(), rem.position()), ("rest", 12));
+    }
+
+    #[test]
+    fn parsers_read_across_chunk_boundaries() {
+        let input = ["ke", "y =", "", " val", "ue;"];
+        let parser = setting;
+
+        let (actual, rem) = parser(Cursor::new(&input)).expect("to parse a setting");
+
+        let expected = (Cow::Owned::<str>("key".to_owned()), Cow::Owned::<str>("value".to_owned()));
+        assert_eq!(actual, expected);
+        assert!(rem.is_at_end());
+    }
+
+    #[test]
+    fn literal_reports_where_it_started() {
+        let input = ["key =", "= value;"];
+        let parser = setting;
+
+        let actual = parser(Cursor::new(&input));
+
+        let expected = Err(ParseError::ExpectingLiteral(" = ".to_owned(), Found::Character(' ')));
+        assert_eq!(actual, expected);
+    }
+}
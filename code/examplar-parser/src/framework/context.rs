@@ -0,0 +1,622 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+
+use super::{Parser, ParseError, ParseResult, Found, SNIPPET_LENGTH};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Budget {
+    max_steps: Option<usize>,
+    max_bytes: Option<usize>,
+}
+
+impl Budget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub steps: usize,
+    pub bytes_examined: usize,
+    pub peak_depth: usize,
+    pub alternatives_tried: usize,
+    pub max_backtrack: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub message: String,
+    pub found: Found,
+}
+
+// Everything needed to put a context and its input back the way they were.
+// Steps and bytes examined are deliberately left out: the work was done
+// whether or not it is rolled back, and budgets have to bound all of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot<'a> {
+    input: &'a str,
+    depth: usize,
+    errors: usize,
+    warnings: usize,
+    memo: usize,
+}
+
+// Results of `memoized` parsers by name and by where the input left starts
+// and how long it is, with the keys in the order they were stored so a
+// restore can drop newer ones.
+type MemoKey = (String, usize, usize);
+
+#[derive(Default)]
+struct Memo {
+    entries: HashMap<MemoKey, Box<dyn Any>>,
+    order: Vec<MemoKey>,
+}
+
+impl Debug for Memo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memo").field("entries", &self.order).finish()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Context {
+    depth: Cell<usize>,
+    peak_depth: Cell<usize>,
+    alternatives: Cell<usize>,
+    max_backtrack: Cell<usize>,
+    budget: Budget,
+    steps: Cell<usize>,
+    bytes: Cell<usize>,
+    errors: RefCell<Vec<ParseError>>,
+    warnings: RefCell<Vec<Warning>>,
+    memo: RefCell<Memo>,
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth.get()
+    }
+
+    pub fn steps(&self) -> usize {
+        self.steps.get()
+    }
+
+    pub fn bytes_examined(&self) -> usize {
+        self.bytes.get()
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            steps: self.steps.get(),
+            bytes_examined: self.bytes.get(),
+            peak_depth: self.peak_depth.get(),
+            alternatives_tried: self.alternatives.get(),
+            max_backtrack: self.max_backtrack.get(),
+        }
+    }
+
+    pub fn report(&self, error: ParseError) {
+        self.errors.borrow_mut().push(error);
+    }
+
+    pub fn take_errors(&self) -> Vec<ParseError> {
+        self.errors.take()
+    }
+
+    pub fn warn(&self, warning: Warning) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    pub fn take_warnings(&self) -> Vec<Warning> {
+        self.warnings.take()
+    }
+
+    pub fn snapshot<'a>(&self, input: &'a str) -> Snapshot<'a> {
+        Snapshot {
+            input,
+            depth: self.depth.get(),
+            errors: self.errors.borrow().len(),
+            warnings: self.warnings.borrow().len(),
+            memo: self.memo.borrow().order.len(),
+        }
+    }
+
+    // Drops errors, warnings and memoized results recorded since the snapshot
+    // and gives back the input to carry on from. Memoized results have to go
+    // too, or a later hit would skip reporting the errors dropped here.
+    pub fn restore<'a>(&self, snapshot: Snapshot<'a>) -> &'a str {
+        self.depth.set(snapshot.depth);
+        self.errors.borrow_mut().truncate(snapshot.errors);
+        self.warnings.borrow_mut().truncate(snapshot.warnings);
+        let mut memo = self.memo.borrow_mut();
+        for key in memo.order.split_off(snapshot.memo) {
+            memo.entries.remove(&key);
+        }
+        snapshot.input
+    }
+
+    fn charge_step(&self) -> Result<(), ParseError> {
+        let steps = self.steps.get() + 1;
+        self.steps.set(steps);
+        match self.budget.max_steps {
+            Some(max_steps) if steps > max_steps => Err(ParseError::BudgetExceeded),
+            _ => Ok(()),
+        }
+    }
+
+    fn charge_bytes(&self, consumed: usize) -> Result<(), ParseError> {
+        let bytes = self.bytes.get() + consumed;
+        self.bytes.set(bytes);
+        match self.budget.max_bytes {
+            Some(max_bytes) if bytes > max_bytes => Err(ParseError::BudgetExceeded),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct DepthLimited<'c, P> {
+    context: &'c Context,
+    max_depth: usize,
+    parser: P,
+}
+
+impl<'a, 'c, T, P> Parser<'a, T> for DepthLimited<'c, P> where P: Parser<'a, T> + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        let depth = self.context.depth.get();
+        if depth >= self.max_depth {
+            return Err(ParseError::RecursionLimitExceeded)
+        }
+        self.context.depth.set(depth + 1);
+        self.context.peak_depth.set(self.context.peak_depth.get().max(depth + 1));
+        let attempt = self.parser.parse(input);
+        self.context.depth.set(depth);
+        attempt
+    }
+}
+
+impl<'c, P> DepthLimited<'c, P> {
+    pub fn new(context: &'c Context, max_depth: usize, parser: P) -> Self {
+        Self { context, max_depth, parser }
+    }
+}
+
+pub fn depth_limited<'a, 'c, T, P>(context: &'c Context, max_depth: usize, parser: P) -> impl Parser<'a, T> + 'c where T: 'a, P: Parser<'a, T> + Sized + 'c {
+    DepthLimited::new(context, max_depth, parser)
+}
+
+pub struct Budgeted<'c, P> {
+    context: &'c Context,
+    parser: P,
+}
+
+impl<'a, 'c, T, P> Parser<'a, T> for Budgeted<'c, P> where P: Parser<'a, T> + Sized {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        self.context.charge_step()?;
+        let (result, rem) = self.parser.parse(input)?;
+        self.context.charge_bytes(input.len() - rem.len())?;
+        Ok((result, rem))
+    }
+}
+
+impl<'c, P> Budgeted<'c, P> {
+    pub fn new(context: &'c Context, parser: P) -> Self {
+        Self { context, parser }
+    }
+}
+
+pub fn budgeted<'a, 'c, T, P>(context: &'c Context, parser: P) -> impl Parser<'a, T> + 'c where T: 'a, P: Parser<'a, T> + Sized + 'c {
+    Budgeted::new(context, parser)
+}
+
+// Counts an attempt at one branch of an alternation. When the branch fails,
+// the bytes that budgeted parsers inside it consumed were wasted, and the
+// largest such amount is kept as the maximum backtrack.
+pub fn alternative<'a, 'c, T, P>(context: &'c Context, parser: P) -> impl Parser<'a, T> + 'c where T: 'a, P: Parser<'a, T> + Sized + 'c {
+    move |input| {
+        context.alternatives.set(context.alternatives.get() + 1);
+        let before = context.bytes.get();
+        let attempt = parser.parse(input);
+        if attempt.is_err() {
+            let wasted = context.bytes.get() - before;
+            context.max_backtrack.set(context.max_backtrack.get().max(wasted));
+        }
+        attempt
+    }
+}
+
+// Like `alternative`, but a failed attempt leaves no errors or warnings
+// behind in the context.
+pub fn speculative<'a, 'c, T, P>(context: &'c Context, parser: P) -> impl Parser<'a, T> + 'c where T: 'a, P: Parser<'a, T> + Sized + 'c {
+    move |input| {
+        let snapshot = context.snapshot(input);
+        let attempt = parser.parse(input);
+        if attempt.is_err() {
+            context.restore(snapshot);
+        }
+        attempt
+    }
+}
+
+// Remembers the result of `parser` at each position, so trying it again after
+// backtracking does not parse the same text twice. Positions are told apart by
+// the address and length of the input left, so a context can be used again on
+// other input. Errors and warnings reported while the parser ran are not
+// reported again on a hit.
+pub fn memoized<'a, 'c, T, P>(context: &'c Context, name: &str, parser: P) -> impl Parser<'a, T> + 'c where T: Clone + 'static, P: Parser<'a, T> + Sized + 'c {
+    let name = name.to_owned();
+    move |input: &'a str| {
+        let key = (name.clone(), input.as_ptr() as usize, input.len());
+        if let Some(result) = context.memo.borrow().entries.get(&key).and_then(|entry| entry.downcast_ref::<Result<(T, usize), ParseError>>()) {
+            return result.clone().map(|(value, consumed)| (value, &input[consumed..]));
+        }
+        let attempt = parser.parse(input);
+        let result = attempt.clone().map(|(value, rem)| (value, input.len() - rem.len()));
+        let mut memo = context.memo.borrow_mut();
+        memo.entries.insert(key.clone(), Box::new(result));
+        memo.order.push(key);
+        attempt
+    }
+}
+
+// Errors are reported as soon as recovery succeeds, so one made inside an
+// alternative that is later abandoned still ends up in the context.
+pub fn recover<'a, 'c, T, U, P, R>(context: &'c Context, parser: P, recovery: R) -> impl Parser<'a, Option<T>> + 'c where T: 'a, U: 'a, P: Parser<'a, T> + Sized + 'c, R: Parser<'a, U> + Sized + 'c {
+    move |input| {
+        match parser.parse(input) {
+            Ok((result, rem)) => Ok((Some(result), rem)),
+            Err(e) if e.is_fatal() && !matches!(e, ParseError::Committed(_)) => Err(e),
+            Err(e) => {
+                let e = match e {
+                    ParseError::Committed(e) => *e,
+                    e => e,
+                };
+                let (_, rem) = recovery.parse(input).map_err(|_| e.clone())?;
+                context.report(e);
+                Ok((None, rem))
+            }
+        }
+    }
+}
+
+// Stands in `default` for input that is missing or wrong, without consuming
+// anything, so the parse can carry on to whatever comes next.
+pub fn expect<'a, 'c, T, P>(context: &'c Context, parser: P, default: T) -> impl Parser<'a, T> + 'c where T: Clone + 'a + 'c, P: Parser<'a, T> + Sized + 'c {
+    move |input| {
+        match parser.parse(input) {
+            Err(e) if !e.is_fatal() => {
+                context.report(e);
+                Ok((default.clone(), input))
+            }
+            attempt => attempt,
+        }
+    }
+}
+
+// Warns about what `parser` matched when `check` has something to say about
+// it. Warnings are not taken back when an enclosing alternative fails, so
+// this is best used where the parse is already committed.
+pub fn warn_if<'a, 'c, T, P, F>(context: &'c Context, parser: P, check: F) -> impl Parser<'a, T> + 'c where T: 'a, P: Parser<'a, T> + Sized + 'c, F: Fn(&T) -> Option<String> + 'c {
+    move |input: &'a str| {
+        let (result, rem) = parser.parse(input)?;
+        if let Some(message) = check(&result) {
+            let found = Found::snippet(&input[..input.len() - rem.len()], SNIPPET_LENGTH);
+            context.warn(Warning { message, found });
+        }
+        Ok((result, rem))
+    }
+}
+
+pub fn parse_warned<'a, T, P>(context: &Context, parser: P, input: &'a str) -> Result<(T, Vec<Warning>), ParseError> where P: Parser<'a, T> + Sized {
+    let attempt = parser.parse(input);
+    let warnings = context.take_warnings();
+    attempt.map(|(result, _)| (result, warnings))
+}
+
+pub fn parse_recovering<'a, T, P>(context: &Context, parser: P, input: &'a str) -> (Option<T>, Vec<ParseError>) where P: Parser<'a, T> + Sized {
+    let attempt = parser.parse(input);
+    let mut errors = context.take_errors();
+    match attempt {
+        Ok((result, "")) => (Some(result), errors),
+        Ok((result, rem)) => {
+            errors.push(ParseError::ExpectingToBeAtEndOfInput(Found::snippet(rem, SNIPPET_LENGTH)));
+            (Some(result), errors)
+        }
+        Err(e) => {
+            errors.push(e);
+            (None, errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{any, at_least, character, cut_after, digit, literal, map, recognize, many, number, one_of, take_until_and_consume};
+
+    fn nesting<'a, 'c>(context: &'c Context) -> impl Parser<'a, ()> + 'c {
+        depth_limited(context, 32, move |input: &'a str| {
+            let (_, rem) = character('(').parse(input)?;
+            let (_, rem) = many(nesting(context)).parse(rem)?;
+            let (_, rem) = character(')').parse(rem)?;
+            Ok(((), rem))
+        })
+    }
+
+    #[test]
+    fn depth_limited_allows_shallow_nesting() {
+        let context = Context::new();
+        let parser = nesting(&context);
+
+        let actual = parser.parse("(()(()))");
+
+        let expected = Ok(((), ""));
+        assert_eq!(actual, expected);
+        assert_eq!(context.depth(), 0);
+        assert_eq!(context.metrics().peak_depth, 4);
+    }
+
+    #[test]
+    fn depth_limited_rejects_deep_nesting() {
+        let input = format!("{}{}", "(".repeat(10_000), ")".repeat(10_000));
+        let context = Context::new();
+        let parser = nesting(&context);
+
+        let actual = parser.parse(&input);
+
+        let expected = Err(ParseError::RecursionLimitExceeded);
+        assert_eq!(actual, expected);
+        assert_eq!(context.depth(), 0);
+    }
+
+    #[test]
+    fn budgeted_counts_steps_and_bytes() {
+        let context = Context::new();
+        let parser = many(budgeted(&context, character('a')));
+
+        let actual = parser.parse("aaab");
+
+        let expected = Ok((vec!['a', 'a', 'a'], "b"));
+        assert_eq!(actual, expected);
+        assert_eq!(context.steps(), 4);
+        assert_eq!(context.bytes_examined(), 3);
+    }
+
+    #[test]
+    fn budgeted_rejects_too_many_steps() {
+        let context = Context::new().with_budget(Budget::new().max_steps(100));
+        let input = "x".repeat(1_000);
+        let parser = many(budgeted(&context, any(|_| true)));
+
+        let actual = parser.parse(&input);
+
+        let expected = Err(ParseError::BudgetExceeded);
+        assert_eq!(actual, expected);
+    }
+
+    fn a_run_ending_in<'a, 'c>(context: &'c Context, end: char) -> impl Parser<'a, char> + 'c {
+        move |input: &'a str| {
+            let (_, rem) = budgeted(context, many(character('a'))).parse(input)?;
+            character(end).parse(rem)
+        }
+    }
+
+    #[test]
+    fn alternative_counts_attempts_and_backtracking() {
+        let context = Context::new();
+        let parser = one_of([alternative(&context, a_run_ending_in(&context, '!')), alternative(&context, a_run_ending_in(&context, '?'))]);
+
+        let actual = parser.parse("aaa?");
+
+        let expected = Ok(('?', ""));
+        assert_eq!(actual, expected);
+        assert_eq!(context.metrics(), Metrics {
+            steps: 2,
+            bytes_examined: 6,
+            peak_depth: 0,
+            alternatives_tried: 2,
+            max_backtrack: 3,
+        });
+    }
+
+    #[test]
+    fn budgeted_counts_backtracked_bytes() {
+        let context = Context::new().with_budget(Budget::new().max_bytes(10));
+        let parser = one_of(vec![a_run_ending_in(&context, '!'), a_run_ending_in(&context, '?')]);
+
+        let actual = parser.parse("aaaaaaaa?");
+
+        let expected = Err(ParseError::BudgetExceeded);
+        assert_eq!(actual, expected);
+    }
+
+    fn statement<'a>() -> impl Parser<'a, u16> {
+        move |input: &'a str| {
+            let (_, rem) = literal("x=").parse(input)?;
+            let (value, rem) = number().parse(rem)?;
+            let (_, rem) = character(';').parse(rem)?;
+            Ok((value, rem))
+        }
+    }
+
+    #[test]
+    fn parse_recovering_collects_every_error() {
+        let input = "x=1;x=?;x=3;y;";
+        let context = Context::new();
+        let parser = many(recover(&context, statement(), take_until_and_consume(";")));
+
+        let actual = parse_recovering(&context, parser, input);
+
+        let expected = (Some(vec![Some(1), None, Some(3), None]), vec![
+            ParseError::ExpectingNamed("digit".to_owned(), Found::Character('?')),
+            ParseError::ExpectingLiteral("x=".to_owned(), Found::Text("y;".to_owned())),
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn recover_stops_committed_errors() {
+        let input = "x=?;x=2;";
+        let context = Context::new();
+        let value = crate::seq!{
+            value = number();
+            _end = character(';');
+            => value
+        };
+        let parser = many(recover(&context, cut_after(literal("x="), value), take_until_and_consume(";")));
+
+        let actual = parse_recovering(&context, parser, input);
+
+        let expected = (Some(vec![None, Some(2)]), vec![ParseError::ExpectingNamed("digit".to_owned(), Found::Character('?'))]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_recovering_reports_unrecoverable_input() {
+        let input = "x=1;x=2";
+        let context = Context::new();
+        let parser = many(recover(&context, statement(), take_until_and_consume(";")));
+
+        let actual = parse_recovering(&context, parser, input);
+
+        let expected = (Some(vec![Some(1)]), vec![
+            ParseError::ExpectingToBeAtEndOfInput(Found::Text("x=2".to_owned())),
+        ]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_warned_returns_warnings_with_the_value() {
+        let input = "x=007;x=8;";
+        let context = Context::new();
+        let value = recognize(at_least(1, digit()));
+        let octal = warn_if(&context, value, |digits: &&str| {
+            if digits.len() > 1 && digits.starts_with('0') { Some("leading zeros are ignored".to_owned()) } else { None }
+        });
+        let parser = many(crate::seq!{
+            _name = literal("x=");
+            value = &octal;
+            _end = character(';');
+            => value
+        });
+
+        let actual = parse_warned(&context, parser, input);
+
+        let expected = Ok((vec!["007", "8"], vec![
+            Warning { message: "leading zeros are ignored".to_owned(), found: Found::Text("007".to_owned()) },
+        ]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn restore_rolls_back_errors_and_warnings_since_the_snapshot() {
+        let input = "let x = ;";
+        let context = Context::new();
+        context.report(ParseError::EndOfInput);
+        let expression = crate::seq!{
+            _let = literal("let x = ");
+            value = expect(&context, number(), 0);
+            _end = character(';');
+            => value
+        };
+
+        let snapshot = context.snapshot(input);
+        let attempt = expression.parse(input);
+        let rewound = context.restore(snapshot);
+
+        assert_eq!(attempt, Ok((0, "")));
+        assert_eq!(rewound, input);
+        assert_eq!(context.take_errors(), vec![ParseError::EndOfInput]);
+    }
+
+    #[test]
+    fn restore_drops_results_memoized_since_the_snapshot() {
+        let input = "12";
+        let context = Context::new();
+        let runs = Cell::new(0);
+        let parser = memoized(&context, "number", |input: &'static str| {
+            runs.set(runs.get() + 1);
+            number().parse(input)
+        });
+
+        let snapshot = context.snapshot(input);
+        let first = parser.parse(input);
+        let hit = parser.parse(input);
+        context.restore(snapshot);
+        let actual = (first, hit, parser.parse(input), runs.get());
+
+        let expected = (Ok((12, "")), Ok((12, "")), Ok((12, "")), 2);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn memoized_tells_inputs_of_the_same_length_apart() {
+        let inputs = ["12+", "345"];
+        let context = Context::new();
+        let parser = memoized(&context, "number", number());
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser.parse(input)).collect();
+
+        let expected = vec![Ok((12, "+")), Ok((345, ""))];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn speculative_forgets_errors_from_a_failed_attempt() {
+        let input = "x=;y";
+        let context = Context::new();
+        let assignment = crate::seq!{
+            _name = literal("x=");
+            value = expect(&context, number(), 0);
+            _end = literal(";;");
+            => value
+        };
+        let parser = crate::choice!{
+            speculative(&context, assignment),
+            map(literal("x=;"), |_| 1),
+        };
+
+        let actual = parse_recovering(&context, parser, input);
+
+        let expected = (Some(1), vec![ParseError::ExpectingToBeAtEndOfInput(Found::Text("y".to_owned()))]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn expect_fills_in_a_default_and_records_the_error() {
+        let input = "x=;";
+        let context = Context::new();
+        let parser = crate::seq!{
+            _name = literal("x=");
+            value = expect(&context, number(), 0);
+            _end = character(';');
+            => value
+        };
+
+        let actual = parse_recovering(&context, parser, input);
+
+        let expected = (Some(0), vec![ParseError::ExpectingNamed("digit".to_owned(), Found::Character(';'))]);
+        assert_eq!(actual, expected);
+    }
+}
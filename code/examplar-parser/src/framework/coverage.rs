@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::fmt::{self, Display, Formatter};
+
+use super::Parser;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleCoverage {
+    pub name: String,
+    pub attempts: usize,
+    pub matches: usize,
+}
+
+// Rules are registered when `covered` builds its parser rather than when they
+// first run, so a rule the corpus never reaches still shows up with no
+// attempts. Registering the same name twice counts both under one entry.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    rules: RefCell<Vec<RuleCoverage>>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn register(&self, name: &str) -> usize {
+        let mut rules = self.rules.borrow_mut();
+        match rules.iter().position(|rule| rule.name == name) {
+            Some(index) => index,
+            None => {
+                rules.push(RuleCoverage { name: name.to_owned(), attempts: 0, matches: 0 });
+                rules.len() - 1
+            }
+        }
+    }
+
+    pub fn rules(&self) -> Vec<RuleCoverage> {
+        self.rules.borrow().clone()
+    }
+
+    // Rules that never matched anything, including those never tried.
+    pub fn unmatched(&self) -> Vec<String> {
+        self.rules.borrow().iter().filter(|rule| rule.matches == 0).map(|rule| rule.name.clone()).collect()
+    }
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rules = self.rules.borrow();
+        let width = rules.iter().map(|rule| rule.name.len()).max().unwrap_or(0);
+        for rule in rules.iter() {
+            writeln!(f, "{:width$}  {} of {} attempts matched", rule.name, rule.matches, rule.attempts, width = width)?;
+        }
+        let matched = rules.iter().filter(|rule| rule.matches > 0).count();
+        write!(f, "{} of {} rules matched", matched, rules.len())
+    }
+}
+
+pub fn covered<'a, 'c, T, P>(coverage: &'c Coverage, name: &str, parser: P) -> impl Parser<'a, T> + 'c where T: 'a, P: Parser<'a, T> + Sized + 'c {
+    let index = coverage.register(name);
+    move |input| {
+        let attempt = parser.parse(input);
+        let mut rules = coverage.rules.borrow_mut();
+        rules[index].attempts += 1;
+        if attempt.is_ok() {
+            rules[index].matches += 1;
+        }
+        attempt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{digit, literal, many, map};
+
+    #[test]
+    fn covered_counts_attempts_and_matches_per_rule() {
+        let inputs = ["12", "+1"];
+        let coverage = Coverage::new();
+        let number = covered(&coverage, "number", many(digit()));
+        let sign = covered(&coverage, "sign", map(literal("-"), |_| vec![]));
+        let _unused = covered(&coverage, "exponent", literal("e"));
+        let parser = crate::choice!{ sign, number };
+
+        for input in inputs.iter() {
+            let _ = parser.parse(input);
+        }
+
+        let actual = (coverage.unmatched(), coverage.to_string());
+
+        let expected = (
+            vec!["sign".to_owned(), "exponent".to_owned()],
+            "number    2 of 2 attempts matched\nsign      0 of 2 attempts matched\nexponent  0 of 0 attempts matched\n1 of 3 rules matched".to_owned(),
+        );
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,9 @@
+pub mod http;
+pub mod shellwords;
+pub mod query;
+pub mod duration;
+pub mod size;
+pub mod color;
+pub mod sexpr;
+pub mod fields;
+pub mod ndjson;
@@ -0,0 +1,189 @@
+use crate::framework::{Found, Parser, ParseError, ParseResult, at_least, character, digit, keywords, many, recognize, spaces, take_while};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+// CSS colours: `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, and the comma
+// separated `rgb()`, `rgba()`, `hsl()` and `hsla()` functions, each of which
+// takes an optional fourth alpha argument. Out of range values are clamped,
+// as CSS does.
+pub fn color<'a>() -> impl Parser<'a, Rgba> {
+    let function = function();
+    move |input: &'a str| {
+        match input.chars().next() {
+            Some('#') => hex(&input[1..]),
+            _ => function.parse(input),
+        }
+    }
+}
+
+fn hex(input: &str) -> ParseResult<'_, Rgba> {
+    let (digits, rem) = take_while(|c| c.is_ascii_hexdigit()).parse(input)?;
+    let value = |index: usize, width: usize| {
+        let digits = &digits[index * width..][..width];
+        let value = u8::from_str_radix(digits, 16).expect("hex digits");
+        if width == 1 { value * 17 } else { value }
+    };
+    let (width, alpha) = match digits.len() {
+        3 => (1, false),
+        4 => (1, true),
+        6 => (2, false),
+        8 => (2, true),
+        _ => return Err(ParseError::ExpectingNamed("3, 4, 6 or 8 hex digits".to_owned(), Found::snippet(input, digits.len()))),
+    };
+    let a = if alpha { value(3, width) } else { 255 };
+    Ok((Rgba { r: value(0, width), g: value(1, width), b: value(2, width), a }, rem))
+}
+
+// A number and whether it was given as a percentage.
+fn argument<'a>() -> impl Parser<'a, (f64, bool)> {
+    let number = recognize(move |input: &'a str| {
+        let rem = input.strip_prefix('-').unwrap_or(input);
+        let (_, rem) = at_least(1, digit()).parse(rem)?;
+        match rem.strip_prefix('.') {
+            Some(rest) => at_least(1, digit()).parse(rest).map(|(_, rem)| ((), rem)),
+            None => Ok(((), rem)),
+        }
+    });
+    move |input: &'a str| {
+        let (number, rem) = number.parse(input)?;
+        let value = number.parse().map_err(|_| ParseError::ExpectingNamed("a number".to_owned(), Found::snippet(input, number.len())))?;
+        match rem.strip_prefix('%') {
+            Some(rem) => Ok(((value, true), rem)),
+            None => Ok(((value, false), rem)),
+        }
+    }
+}
+
+fn arguments<'a>() -> impl Parser<'a, Vec<(f64, bool)>> {
+    let rest = many(move |input: &'a str| {
+        let (_, rem) = spaces().parse(input)?;
+        let (_, rem) = character(',').parse(rem)?;
+        let (_, rem) = spaces().parse(rem)?;
+        argument().parse(rem)
+    });
+    move |input: &'a str| {
+        let (_, rem) = character('(').parse(input)?;
+        let (_, rem) = spaces().parse(rem)?;
+        let (first, rem) = argument().parse(rem)?;
+        let (mut arguments, rem) = rest.parse(rem)?;
+        let (_, rem) = spaces().parse(rem)?;
+        let (_, rem) = character(')').parse(rem)?;
+        arguments.insert(0, first);
+        Ok((arguments, rem))
+    }
+}
+
+fn function<'a>() -> impl Parser<'a, Rgba> {
+    let name = keywords(["rgb", "rgba", "hsl", "hsla"]);
+    let arguments = arguments();
+    move |input: &'a str| {
+        let (name, rem) = name.parse(input)?;
+        let (values, rest) = arguments.parse(rem)?;
+        let (r, g, b, alpha) = match values.as_slice() {
+            [x, y, z] => (*x, *y, *z, None),
+            [x, y, z, alpha] => (*x, *y, *z, Some(*alpha)),
+            _ => return Err(ParseError::ExpectingNamed("3 or 4 arguments".to_owned(), Found::snippet(rem, rem.len() - rest.len()))),
+        };
+        let a = match alpha {
+            Some((alpha, true)) => channel(alpha / 100.0),
+            Some((alpha, false)) => channel(alpha),
+            None => 255,
+        };
+        let (r, g, b) = if name.starts_with("hsl") {
+            hsl_to_rgb(r.0, g.0 / 100.0, b.0 / 100.0)
+        } else {
+            let fraction = |(value, percent): (f64, bool)| if percent { value / 100.0 } else { value / 255.0 };
+            (channel(fraction(r)), channel(fraction(g)), channel(fraction(b)))
+        };
+        Ok((Rgba { r, g, b, a }, rest))
+    }
+}
+
+fn channel(fraction: f64) -> u8 {
+    (fraction.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let (saturation, lightness) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second),
+    };
+    let lightest = lightness - chroma / 2.0;
+    (channel(r + lightest), channel(g + lightest), channel(b + lightest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(r: u8, g: u8, b: u8, a: u8) -> Rgba {
+        Rgba { r, g, b, a }
+    }
+
+    #[test]
+    fn color_parses_hex_forms() {
+        let parser = color();
+
+        assert_eq!(parser.parse("#f80"), Ok((rgba(255, 136, 0, 255), "")));
+        assert_eq!(parser.parse("#0f08"), Ok((rgba(0, 255, 0, 136), "")));
+        assert_eq!(parser.parse("#102030;"), Ok((rgba(16, 32, 48, 255), ";")));
+        assert_eq!(parser.parse("#10203040"), Ok((rgba(16, 32, 48, 64), "")));
+    }
+
+    #[test]
+    fn color_rejects_other_hex_lengths() {
+        let input = "#12345";
+        let parser = color();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("3, 4, 6 or 8 hex digits".to_owned(), Found::Text("12345".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn color_parses_functions() {
+        let parser = color();
+
+        assert_eq!(parser.parse("rgb(255, 0, 300)"), Ok((rgba(255, 0, 255, 255), "")));
+        assert_eq!(parser.parse("rgba( 100%,50%,0% , 0.5 )"), Ok((rgba(255, 128, 0, 128), "")));
+        assert_eq!(parser.parse("hsl(120, 100%, 50%)"), Ok((rgba(0, 255, 0, 255), "")));
+        assert_eq!(parser.parse("hsla(-120, 100%, 25%, 50%)"), Ok((rgba(0, 0, 128, 128), "")));
+    }
+
+    #[test]
+    fn color_allows_a_single_minus_sign() {
+        let input = "rgb(--5, 0, 0)";
+        let parser = color();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("digit".to_owned(), Found::Character('-')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn color_checks_the_number_of_arguments() {
+        let input = "rgb(1, 2)";
+        let parser = color();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("3 or 4 arguments".to_owned(), Found::Text("(1, 2)".to_owned())));
+        assert_eq!(actual, expected);
+    }
+}
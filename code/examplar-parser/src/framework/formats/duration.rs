@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use crate::framework::{Found, Parser, ParseError, ParseResult, at_least, digit, recognize, take_while};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 8] = [
+        Unit::Nanoseconds, Unit::Microseconds, Unit::Milliseconds, Unit::Seconds,
+        Unit::Minutes, Unit::Hours, Unit::Days, Unit::Weeks,
+    ];
+
+    fn suffixes(self) -> &'static [&'static str] {
+        match self {
+            Unit::Nanoseconds => &["ns"],
+            Unit::Microseconds => &["us", "µs"],
+            Unit::Milliseconds => &["ms"],
+            Unit::Seconds => &["s"],
+            Unit::Minutes => &["m"],
+            Unit::Hours => &["h"],
+            Unit::Days => &["d"],
+            Unit::Weeks => &["w"],
+        }
+    }
+
+    fn nanoseconds(self) -> u128 {
+        match self {
+            Unit::Nanoseconds => 1,
+            Unit::Microseconds => 1_000,
+            Unit::Milliseconds => 1_000_000,
+            Unit::Seconds => 1_000_000_000,
+            Unit::Minutes => 60 * Unit::Seconds.nanoseconds(),
+            Unit::Hours => 60 * Unit::Minutes.nanoseconds(),
+            Unit::Days => 24 * Unit::Hours.nanoseconds(),
+            Unit::Weeks => 7 * Unit::Days.nanoseconds(),
+        }
+    }
+}
+
+// One or more amounts with a unit each, e.g. `1h30m`, `2d 4h` or `1500ms`,
+// optionally separated by spaces. The longest suffix wins, so `ms` is never
+// read as minutes followed by seconds, and a suffix followed by a letter is
+// not a unit at all.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HumanDuration {
+    suffixes: Vec<(&'static str, Unit)>,
+}
+
+impl<'a> Parser<'a, Duration> for HumanDuration {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Duration> {
+        let amount = recognize(at_least(1, digit()));
+        let mut rem = input;
+        let mut total: u128 = 0;
+        loop {
+            let (digits, rest) = amount.parse(rem)?;
+            let (unit, rest) = self.unit(rest)?;
+            let part = &rem[..rem.len() - rest.len()];
+            total = digits.parse::<u128>().ok()
+                .and_then(|amount| amount.checked_mul(unit.nanoseconds()))
+                .and_then(|nanoseconds| total.checked_add(nanoseconds))
+                .filter(|total| total / 1_000_000_000 <= u64::MAX as u128)
+                .ok_or_else(|| ParseError::Overflow(Found::Text(part.to_owned())))?;
+            let (_, next) = take_while(|c| c == ' ').parse(rest)?;
+            match next.chars().next() {
+                Some(c) if c.is_ascii_digit() => rem = next,
+                _ => {
+                    let duration = Duration::new((total / 1_000_000_000) as u64, (total % 1_000_000_000) as u32);
+                    return Ok((duration, rest));
+                }
+            }
+        }
+    }
+}
+
+impl Default for HumanDuration {
+    fn default() -> Self {
+        Self::new(Unit::ALL.iter().copied())
+    }
+}
+
+impl HumanDuration {
+    pub fn new<I>(units: I) -> Self where I: IntoIterator<Item = Unit> {
+        let mut suffixes: Vec<_> = units.into_iter()
+            .flat_map(|unit| unit.suffixes().iter().map(move |suffix| (*suffix, unit)))
+            .collect();
+        suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+        Self { suffixes }
+    }
+
+    fn unit<'a>(&self, input: &'a str) -> ParseResult<'a, Unit> {
+        self.suffixes.iter()
+            .filter_map(|(suffix, unit)| input.strip_prefix(suffix).map(|rest| (*unit, rest)))
+            .find(|(_, rest)| !rest.starts_with(char::is_alphabetic))
+            .ok_or_else(|| ParseError::ExpectingNamed("a time unit".to_owned(), Found::at(input)))
+    }
+}
+
+pub fn duration() -> HumanDuration {
+    HumanDuration::default()
+}
+
+pub fn duration_in<I>(units: I) -> HumanDuration where I: IntoIterator<Item = Unit> {
+    HumanDuration::new(units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_adds_up_its_parts() {
+        let input = "2d 4h30m15s;";
+        let parser = duration();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((Duration::from_secs(((2 * 24 + 4) * 60 + 30) * 60 + 15), ";"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn duration_prefers_the_longest_unit() {
+        let input = "1500ms 1m";
+        let parser = duration();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((Duration::from_millis(61_500), ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn duration_in_only_accepts_the_given_units() {
+        let input = "1h30m";
+        let parser = duration_in([Unit::Minutes, Unit::Seconds]);
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("a time unit".to_owned(), Found::Character('h')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn duration_reports_overflow() {
+        let input = "1s 99999999999999999999w";
+        let parser = duration();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::Overflow(Found::Text("99999999999999999999w".to_owned())));
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,139 @@
+use crate::framework::{Found, Parser, ParseError, blank_line, eol_or_eof, line, literal, newline};
+
+// `key: value` lines in the order they were given, with repeated keys kept.
+// Lookups ignore ASCII case, as header names do in email and HTTP.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fields<'a>(Vec<(&'a str, String)>);
+
+impl<'a> Fields<'a> {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    pub fn get_all<'f>(&'f self, name: &'f str) -> impl Iterator<Item = &'f str> + 'f {
+        self.iter().filter(move |(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &str)> {
+        self.0.iter().map(|(key, value)| (*key, value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // A line starting with whitespace continues the previous value; the line
+    // break and indentation fold into a single space.
+    fn add_line(&mut self, text: &'a str, at: &'a str) -> Result<(), ParseError> {
+        if text.starts_with([' ', '\t']) {
+            let (_, value) = self.0.last_mut().ok_or_else(|| ParseError::ExpectingNamed("a field name".to_owned(), Found::at(at)))?;
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(text.trim());
+            return Ok(());
+        }
+        let colon = text.find(':').ok_or_else(|| ParseError::ExpectingCharacter(':', Found::snippet(at, text.len())))?;
+        let key = &text[..colon];
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            return Err(ParseError::ExpectingNamed("a field name".to_owned(), Found::at(at)));
+        }
+        self.0.push((key, text[colon + 1..].trim().to_owned()));
+        Ok(())
+    }
+}
+
+// Fields up to and including the blank line that ends them, or up to the
+// end of the input.
+pub fn header_block<'a>() -> impl Parser<'a, Fields<'a>> {
+    move |input: &'a str| {
+        let mut fields = Fields::default();
+        let mut rem = input;
+        while !rem.is_empty() {
+            if let Ok((_, rest)) = blank_line().parse(rem) {
+                return Ok((fields, rest));
+            }
+            let (text, rest) = line().parse(rem)?;
+            let (_, rest) = eol_or_eof().parse(rest)?;
+            fields.add_line(text, rem)?;
+            rem = rest;
+        }
+        Ok((fields, rem))
+    }
+}
+
+// Fields between two `---` lines at the start of a document, as Markdown
+// front matter is written. Blank lines inside are ignored.
+pub fn front_matter<'a>() -> impl Parser<'a, Fields<'a>> {
+    move |input: &'a str| {
+        let (_, rem) = literal("---").parse(input)?;
+        let (_, mut rem) = newline().parse(rem)?;
+        let mut fields = Fields::default();
+        loop {
+            let (text, rest) = line().parse(rem).map_err(|_| ParseError::ExpectingLiteral("---".to_owned(), Found::EndOfInput))?;
+            let (_, rest) = eol_or_eof().parse(rest)?;
+            if text == "---" {
+                return Ok((fields, rest));
+            }
+            if !text.trim().is_empty() {
+                fields.add_line(text, rem)?;
+            }
+            rem = rest;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_block_folds_continuation_lines() {
+        let input = "From: someone\r\nReceived: by a\r\n\tby b\r\nreceived: by c\r\n\r\nbody";
+        let parser = header_block();
+
+        let (fields, rem) = parser.parse(input).expect("to parse a header block");
+
+        assert_eq!(fields.iter().collect::<Vec<_>>(), vec![("From", "someone"), ("Received", "by a by b"), ("received", "by c")]);
+        assert_eq!(fields.get_all("RECEIVED").collect::<Vec<_>>(), vec!["by a by b", "by c"]);
+        assert_eq!(fields.get("from"), Some("someone"));
+        assert_eq!(rem, "body");
+    }
+
+    #[test]
+    fn header_block_rejects_a_line_without_a_colon() {
+        let input = "Subject: hi\nnot a header\n";
+        let parser = header_block();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingCharacter(':', Found::Text("not a header".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn front_matter_runs_between_the_markers() {
+        let input = "---\ntitle: Notes\n\ntags: a, b\n---\n# Notes\n";
+        let parser = front_matter();
+
+        let (fields, rem) = parser.parse(input).expect("to parse front matter");
+
+        assert_eq!(fields.iter().collect::<Vec<_>>(), vec![("title", "Notes"), ("tags", "a, b")]);
+        assert_eq!(rem, "# Notes\n");
+    }
+
+    #[test]
+    fn front_matter_must_be_closed() {
+        let input = "---\ntitle: Notes\n";
+        let parser = front_matter();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingLiteral("---".to_owned(), Found::EndOfInput));
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,254 @@
+use crate::framework::ParseError;
+use crate::framework::bytes::{ByteResult, byte, found, tag, take_while, take_while1, u8};
+
+// Messages are bytes: header values and reason phrases may contain obs-text
+// (0x80 to 0xFF), which need not be UTF-8. Every element ends in a delimiter,
+// so running out of input part way through one is `Incomplete`, which a
+// caller reading from a socket answers by reading more and trying again.
+// Line endings are CRLF, but a bare LF is accepted too, as RFC 9112 allows
+// recipients to do.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestLine<'a> {
+    pub method: &'a str,
+    pub target: &'a str,
+    pub version: Version,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusLine<'a> {
+    pub version: Version,
+    pub status: u16,
+    pub reason: &'a [u8],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header<'a> {
+    pub name: &'a str,
+    pub value: &'a [u8],
+}
+
+fn is_tchar(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
+fn is_vchar(byte: u8) -> bool {
+    byte.is_ascii_graphic() || byte >= 0x80
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    byte == b' ' || byte == b'\t'
+}
+
+fn is_field_content(byte: u8) -> bool {
+    is_vchar(byte) || is_whitespace(byte)
+}
+
+// Tokens and targets are checked to be ASCII before they get here.
+fn ascii(bytes: &[u8]) -> &str {
+    std::str::from_utf8(bytes).expect("ASCII to be UTF-8")
+}
+
+fn digit(input: &[u8]) -> ByteResult<'_, u8> {
+    match u8(input)? {
+        (byte, rem) if byte.is_ascii_digit() => Ok((byte - b'0', rem)),
+        _ => Err(ParseError::ExpectingNamed("digit".to_owned(), found(input))),
+    }
+}
+
+fn crlf(input: &[u8]) -> ByteResult<'_, u8> {
+    match input.first() {
+        Some(b'\r') => byte(b'\n')(&input[1..]),
+        _ => byte(b'\n')(input),
+    }
+}
+
+pub fn token(input: &[u8]) -> ByteResult<'_, &str> {
+    let (token, rem) = take_while1("a token character", is_tchar)(input)?;
+    Ok((ascii(token), rem))
+}
+
+pub fn quoted_string(input: &[u8]) -> ByteResult<'_, Vec<u8>> {
+    let (_, mut rem) = byte(b'"')(input)?;
+    let mut value = vec![];
+    loop {
+        match rem {
+            [b'"', rest @ ..] => return Ok((value, rest)),
+            [b'\\', byte, rest @ ..] if is_field_content(*byte) => {
+                value.push(*byte);
+                rem = rest;
+            }
+            [byte, rest @ ..] if *byte != b'\\' && is_field_content(*byte) => {
+                value.push(*byte);
+                rem = rest;
+            }
+            [] | [b'\\'] => return Err(ParseError::Incomplete),
+            _ => return Err(ParseError::ExpectingCharacter('"', found(rem))),
+        }
+    }
+}
+
+pub fn version(input: &[u8]) -> ByteResult<'_, Version> {
+    let (_, rem) = tag(b"HTTP/")(input)?;
+    let (major, rem) = digit(rem)?;
+    let (_, rem) = byte(b'.')(rem)?;
+    let (minor, rem) = digit(rem)?;
+    Ok((Version { major, minor }, rem))
+}
+
+pub fn request_line(input: &[u8]) -> ByteResult<'_, RequestLine<'_>> {
+    let (method, rem) = token(input)?;
+    let (_, rem) = byte(b' ')(rem)?;
+    let (target, rem) = take_while1("a request target", |byte: u8| byte.is_ascii_graphic())(rem)?;
+    let (_, rem) = byte(b' ')(rem)?;
+    let (version, rem) = version(rem)?;
+    let (_, rem) = crlf(rem)?;
+    Ok((RequestLine { method, target: ascii(target), version }, rem))
+}
+
+pub fn status_line(input: &[u8]) -> ByteResult<'_, StatusLine<'_>> {
+    let (version, rem) = version(input)?;
+    let (_, rem) = byte(b' ')(rem)?;
+    let (hundreds, rem) = digit(rem)?;
+    let (tens, rem) = digit(rem)?;
+    let (ones, rem) = digit(rem)?;
+    let (_, rem) = byte(b' ')(rem)?;
+    let (reason, rem) = take_while(is_field_content)(rem)?;
+    let (_, rem) = crlf(rem)?;
+    let status = u16::from(hundreds) * 100 + u16::from(tens) * 10 + u16::from(ones);
+    Ok((StatusLine { version, status, reason }, rem))
+}
+
+// Whitespace around the value is not part of it. Obsolete line folding is
+// not supported, so a line starting with whitespace is not a header.
+pub fn header_field(input: &[u8]) -> ByteResult<'_, Header<'_>> {
+    let (name, rem) = token(input)?;
+    let (_, rem) = byte(b':')(rem)?;
+    let (_, rem) = take_while(is_whitespace)(rem)?;
+    let (value, rem) = take_while(is_field_content)(rem)?;
+    let (_, rem) = crlf(rem)?;
+    let end = value.iter().rposition(|byte| !is_whitespace(*byte)).map_or(0, |last| last + 1);
+    Ok((Header { name, value: &value[..end] }, rem))
+}
+
+// The header section runs up to and including the empty line that ends it.
+pub fn headers(input: &[u8]) -> ByteResult<'_, Vec<Header<'_>>> {
+    let mut headers = vec![];
+    let mut rem = input;
+    loop {
+        if let Ok((_, rest)) = crlf(rem) {
+            return Ok((headers, rest));
+        }
+        match header_field(rem) {
+            Ok((header, rest)) => {
+                headers.push(header);
+                rem = rest;
+            }
+            Err(ParseError::Incomplete) => return Err(ParseError::Incomplete),
+            Err(_) => return Err(ParseError::ExpectingNamed("a header field".to_owned(), found(rem))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::Found;
+
+    #[test]
+    fn request_line_parses_method_target_and_version() {
+        let input = b"GET /index.html?q=1 HTTP/1.1\r\nHost: example.com\r\n";
+        let parser = request_line;
+
+        let actual = parser(input);
+
+        let expected = Ok((RequestLine { method: "GET", target: "/index.html?q=1", version: Version { major: 1, minor: 1 } }, &b"Host: example.com\r\n"[..]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn request_line_is_incomplete_when_cut_short() {
+        let inputs: [&[u8]; 4] = [b"GET /ind", b"GET / HTT", b"GET / HTTP/1.1\r", b""];
+        let parser = request_line;
+
+        let actual: Vec<_> = inputs.iter().map(|input| parser(input)).collect();
+
+        let expected = vec![Err(ParseError::Incomplete); 4];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn status_line_allows_an_empty_reason() {
+        let input = b"HTTP/1.0 204 \r\n";
+        let parser = status_line;
+
+        let actual = parser(input);
+
+        let expected = Ok((StatusLine { version: Version { major: 1, minor: 0 }, status: 204, reason: &b""[..] }, &b""[..]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn headers_run_to_the_empty_line() {
+        let input = b"Host: example.com\r\nAccept:  text/html \r\n\r\nbody";
+        let parser = headers;
+
+        let actual = parser(input);
+
+        let expected = Ok((vec![
+            Header { name: "Host", value: b"example.com" },
+            Header { name: "Accept", value: b"text/html" },
+        ], &b"body"[..]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn headers_keep_obs_text_as_bytes() {
+        let input = b"Title: caf\xe9\r\n\r\n";
+        let parser = headers;
+
+        let actual = parser(input);
+
+        let expected = Ok((vec![Header { name: "Title", value: b"caf\xe9" }], &b""[..]));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn headers_are_incomplete_without_the_empty_line() {
+        let input = b"Host: example.com\r\n";
+        let parser = headers;
+
+        let actual = parser(input);
+
+        let expected = Err(ParseError::Incomplete);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn headers_reject_a_malformed_field() {
+        let input = b"Host example.com\r\n\r\n";
+        let parser = headers;
+
+        let actual = parser(input);
+
+        let expected = Err(ParseError::ExpectingNamed("a header field".to_owned(), Found::Character('H')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn quoted_string_unescapes_quoted_pairs() {
+        let input = br#""say \"hi\"";"#;
+        let parser = quoted_string;
+
+        let actual = parser(input);
+
+        let expected = Ok((br#"say "hi""#.to_vec(), &b";"[..]));
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,292 @@
+use std::io::{self, BufRead};
+
+use crate::framework::{Found, Parser, ParseError, ParseResult, SNIPPET_LENGTH};
+
+// Objects keep their members in the order they were written, duplicates
+// included, so a record can be written back out the way it came in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+// Arrays and objects are parsed recursively, so a line of nothing but `[`
+// would otherwise run out of stack and take the whole reader down with it.
+const MAX_DEPTH: usize = 128;
+
+fn skip_whitespace(input: &str) -> &str {
+    input.trim_start_matches(|c| " \t\r\n".contains(c))
+}
+
+fn expecting(what: &str, input: &str) -> ParseError {
+    ParseError::ExpectingNamed(what.to_owned(), Found::at(input))
+}
+
+fn digits(input: &str) -> &str {
+    input.trim_start_matches(|c: char| c.is_ascii_digit())
+}
+
+fn number(input: &str) -> ParseResult<'_, Json> {
+    let mut rem = input.strip_prefix('-').unwrap_or(input);
+    rem = match rem.strip_prefix('0') {
+        Some(rest) => rest,
+        None if rem.starts_with(|c: char| c.is_ascii_digit()) => digits(rem),
+        None => return Err(expecting("a number", input)),
+    };
+    if let Some(fraction) = rem.strip_prefix('.') {
+        rem = digits(fraction);
+        if rem.len() == fraction.len() {
+            return Err(expecting("a digit", fraction));
+        }
+    }
+    if let Some(exponent) = rem.strip_prefix(|c| c == 'e' || c == 'E') {
+        let exponent = exponent.strip_prefix(|c| c == '+' || c == '-').unwrap_or(exponent);
+        rem = digits(exponent);
+        if rem.len() == exponent.len() {
+            return Err(expecting("a digit", exponent));
+        }
+    }
+    let text = &input[..input.len() - rem.len()];
+    let value = text.parse().map_err(|_| ParseError::Overflow(Found::snippet(text, SNIPPET_LENGTH)))?;
+    Ok((Json::Number(value), rem))
+}
+
+fn hex4(input: &str) -> ParseResult<'_, u32> {
+    match input.get(..4).filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit())) {
+        Some(hex) => Ok((u32::from_str_radix(hex, 16).expect("four hex digits"), &input[4..])),
+        None => Err(expecting("four hex digits", input)),
+    }
+}
+
+// A `\u` escape outside the basic multilingual plane is written as a
+// surrogate pair, which has to be put back together into one character.
+fn unicode_escape(input: &str) -> ParseResult<'_, char> {
+    let (high, rem) = hex4(input)?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return char::from_u32(high).map(|c| (c, rem)).ok_or_else(|| expecting("a character", input));
+    }
+    let (low, rest) = rem.strip_prefix("\\u").map_or(Err(expecting("a low surrogate", rem)), hex4)?;
+    if !(0xDC00..0xE000).contains(&low) {
+        return Err(expecting("a low surrogate", rem));
+    }
+    let c = char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).expect("a valid surrogate pair");
+    Ok((c, rest))
+}
+
+fn string(input: &str) -> ParseResult<'_, String> {
+    let mut rem = input.strip_prefix('"').ok_or_else(|| ParseError::ExpectingCharacter('"', Found::at(input)))?;
+    let mut text = String::new();
+    loop {
+        let mut chars = rem.chars();
+        match chars.next() {
+            Some('"') => return Ok((text, chars.as_str())),
+            Some('\\') => {
+                let escaped = chars.as_str();
+                let (c, rest) = match chars.next() {
+                    Some('u') => unicode_escape(chars.as_str())?,
+                    Some(c @ '"') | Some(c @ '\\') | Some(c @ '/') => (c, chars.as_str()),
+                    Some('b') => ('\u{8}', chars.as_str()),
+                    Some('f') => ('\u{c}', chars.as_str()),
+                    Some('n') => ('\n', chars.as_str()),
+                    Some('r') => ('\r', chars.as_str()),
+                    Some('t') => ('\t', chars.as_str()),
+                    _ => return Err(expecting("an escape sequence", escaped)),
+                };
+                text.push(c);
+                rem = rest;
+            }
+            Some(c) if c >= ' ' => {
+                text.push(c);
+                rem = chars.as_str();
+            }
+            _ => return Err(ParseError::ExpectingCharacter('"', Found::at(rem))),
+        }
+    }
+}
+
+// Elements and members are both separated by commas and closed by `close`.
+fn items<T>(input: &str, close: char, depth: usize, item: impl Fn(&str, usize) -> ParseResult<'_, T>) -> ParseResult<'_, Vec<T>> {
+    let mut items = vec![];
+    let mut rem = skip_whitespace(input);
+    if let Some(rest) = rem.strip_prefix(close) {
+        return Ok((items, rest));
+    }
+    loop {
+        let (value, rest) = item(skip_whitespace(rem), depth)?;
+        items.push(value);
+        rem = skip_whitespace(rest);
+        match rem.chars().next() {
+            Some(',') => rem = &rem[1..],
+            Some(c) if c == close => return Ok((items, &rem[1..])),
+            _ => return Err(expecting(&format!("',' or '{}'", close), rem)),
+        }
+    }
+}
+
+fn member(input: &str, depth: usize) -> ParseResult<'_, (String, Json)> {
+    let (key, rem) = string(input)?;
+    let rem = skip_whitespace(rem);
+    let rem = rem.strip_prefix(':').ok_or_else(|| ParseError::ExpectingCharacter(':', Found::at(rem)))?;
+    let (value, rem) = value(skip_whitespace(rem), depth)?;
+    Ok(((key, value), rem))
+}
+
+pub fn json(input: &str) -> ParseResult<'_, Json> {
+    value(input, 0)
+}
+
+fn value(input: &str, depth: usize) -> ParseResult<'_, Json> {
+    if depth >= MAX_DEPTH {
+        return Err(ParseError::RecursionLimitExceeded);
+    }
+    let keyword = |word: &str, value: Json| match input.strip_prefix(word) {
+        Some(rem) => Ok((value, rem)),
+        None => Err(ParseError::ExpectingLiteral(word.to_owned(), Found::snippet(input, word.len()))),
+    };
+    match input.chars().next() {
+        Some('n') => keyword("null", Json::Null),
+        Some('t') => keyword("true", Json::Bool(true)),
+        Some('f') => keyword("false", Json::Bool(false)),
+        Some('"') => string(input).map(|(text, rem)| (Json::String(text), rem)),
+        Some('[') => items(&input[1..], ']', depth + 1, value).map(|(values, rem)| (Json::Array(values), rem)),
+        Some('{') => items(&input[1..], '}', depth + 1, member).map(|(members, rem)| (Json::Object(members), rem)),
+        Some(c) if c == '-' || c.is_ascii_digit() => number(input),
+        _ => Err(expecting("a JSON value", input)),
+    }
+}
+
+// A whole line holding one value, with nothing but whitespace around it.
+pub fn record(line: &str) -> Result<Json, ParseError> {
+    let (value, rem) = json.parse(skip_whitespace(line))?;
+    match skip_whitespace(rem) {
+        "" => Ok(value),
+        rem => Err(ParseError::ExpectingToBeAtEndOfInput(Found::snippet(rem, SNIPPET_LENGTH))),
+    }
+}
+
+// `line` counts from one, so it can be shown as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub line: usize,
+    pub value: Result<Json, ParseError>,
+}
+
+// Reads one record per line, skipping blank lines. A malformed line is handed
+// out with its error and the reader carries on with the next one, as it does
+// after a line that could not be read, which still counts as a line.
+pub struct Records<R> {
+    reader: R,
+    line: usize,
+    buffer: String,
+}
+
+impl<R: BufRead> Records<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: 0, buffer: String::new() }
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => {
+                    self.line += 1;
+                    return Some(Err(e));
+                }
+            }
+            if !self.buffer.trim().is_empty() {
+                let line = self.buffer.trim_end_matches(&['\r', '\n'][..]);
+                return Some(Ok(Record { line: self.line, value: record(line) }));
+            }
+        }
+    }
+}
+
+pub fn records<R: BufRead>(reader: R) -> Records<R> {
+    Records::new(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_parses_nested_values() {
+        let input = r#"{"a": [1, -2.5e1, true, null], "b": "x\né😀"} rest"#;
+        let parser = json;
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((Json::Object(vec![
+            ("a".to_owned(), Json::Array(vec![Json::Number(1.0), Json::Number(-25.0), Json::Bool(true), Json::Null])),
+            ("b".to_owned(), Json::String("x\né😀".to_owned())),
+        ]), " rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn json_rejects_a_missing_separator() {
+        let input = "[1 2]";
+        let parser = json;
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("',' or ']'".to_owned(), Found::Character('2')));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn records_report_malformed_lines_and_carry_on() {
+        let input = "{\"id\": 1}\n\n{\"id\": }\n[true] x\n\"last\"";
+        let reader = io::Cursor::new(input);
+
+        let actual: Vec<_> = records(reader).map(|record| record.expect("to read from memory")).collect();
+
+        let expected = vec![
+            Record { line: 1, value: Ok(Json::Object(vec![("id".to_owned(), Json::Number(1.0))])) },
+            Record { line: 3, value: Err(ParseError::ExpectingNamed("a JSON value".to_owned(), Found::Character('}'))) },
+            Record { line: 4, value: Err(ParseError::ExpectingToBeAtEndOfInput(Found::Text("x".to_owned()))) },
+            Record { line: 5, value: Ok(Json::String("last".to_owned())) },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn records_stop_deep_nesting_at_the_line() {
+        let input = format!("{}\n[1]\n", "[".repeat(200_000));
+        let reader = io::Cursor::new(input);
+
+        let actual: Vec<_> = records(reader).map(|record| record.expect("to read from memory")).collect();
+
+        let expected = vec![
+            Record { line: 1, value: Err(ParseError::RecursionLimitExceeded) },
+            Record { line: 2, value: Ok(Json::Array(vec![Json::Number(1.0)])) },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn records_count_lines_that_could_not_be_read() {
+        let input = b"1\n\xff\n2\n".to_vec();
+        let reader = io::Cursor::new(input);
+
+        let actual: Vec<_> = records(reader).map(|record| record.map_err(|e| e.kind())).collect();
+
+        let expected = vec![
+            Ok(Record { line: 1, value: Ok(Json::Number(1.0)) }),
+            Err(io::ErrorKind::InvalidData),
+            Ok(Record { line: 3, value: Ok(Json::Number(2.0)) }),
+        ];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::framework::{Found, Parser, ParseError, ParseResult, take_while};
+
+// `application/x-www-form-urlencoded` as browsers send it: pairs are joined by
+// `&`, `+` stands for a space and `%XX` for a byte. Malformed escapes are kept
+// as they are and bytes that are not UTF-8 are replaced, so decoding itself
+// never fails. The query ends at a `#` or at the end of the input.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Repeated {
+    KeepAll,
+    First,
+    Last,
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Missing {
+    Empty,
+    Skip,
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Query {
+    repeated: Repeated,
+    missing: Missing,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self { repeated: Repeated::KeepAll, missing: Missing::Empty }
+    }
+}
+
+impl<'a> Parser<'a, Vec<(String, String)>> for Query {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Vec<(String, String)>> {
+        let mut pairs: Vec<(String, String)> = vec![];
+        let mut rem = input;
+        loop {
+            let (raw_key, rest) = take_while(|c| !"&=#".contains(c)).parse(rem)?;
+            let (raw_value, rest) = match rest.strip_prefix('=') {
+                Some(rest) => {
+                    let (raw_value, rest) = take_while(|c| !"&#".contains(c)).parse(rest)?;
+                    (Some(raw_value), rest)
+                }
+                None => (None, rest),
+            };
+            let value = match (raw_value, self.missing) {
+                (None, _) if raw_key.is_empty() => None,
+                (Some(value), _) => Some(decode(value)),
+                (None, Missing::Empty) => Some(String::new()),
+                (None, Missing::Skip) => None,
+                (None, Missing::Reject) => return Err(ParseError::ExpectingCharacter('=', Found::at(rest))),
+            };
+            if let Some(value) = value {
+                let key = decode(raw_key);
+                match (pairs.iter().position(|(k, _)| *k == key), self.repeated) {
+                    (Some(_), Repeated::First) => {}
+                    (Some(index), Repeated::Last) => pairs[index].1 = value,
+                    (Some(_), Repeated::Reject) => return Err(ParseError::ExpectingNamed("a key that is not repeated".to_owned(), Found::Text(raw_key.to_owned()))),
+                    _ => pairs.push((key, value)),
+                }
+            }
+            match rest.strip_prefix('&') {
+                Some(rest) => rem = rest,
+                None => return Ok((pairs, rest)),
+            }
+        }
+    }
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn repeated(mut self, repeated: Repeated) -> Self {
+        self.repeated = repeated;
+        self
+    }
+
+    pub fn missing(mut self, missing: Missing) -> Self {
+        self.missing = missing;
+        self
+    }
+}
+
+pub fn query() -> Query {
+    Query::new()
+}
+
+fn hex_value(digit: u8) -> u8 {
+    (digit as char).to_digit(16).expect("a hex digit") as u8
+}
+
+pub fn decode(text: &str) -> String {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let escaped = match tail {
+            [high, low, ..] if byte == b'%' && high.is_ascii_hexdigit() && low.is_ascii_hexdigit() => Some(hex_value(*high) * 16 + hex_value(*low)),
+            _ => None,
+        };
+        match escaped {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(if byte == b'+' { b' ' } else { byte });
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn query_decodes_keys_and_values() {
+        let input = "a=1&b=two%20words&c+d=%E2%9C%93&&e#top";
+        let parser = query();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((pairs(&[("a", "1"), ("b", "two words"), ("c d", "✓"), ("e", "")]), "#top"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn query_can_keep_the_last_of_repeated_keys_and_skip_missing_values() {
+        let input = "a=1&b&a=2";
+        let parser = query().repeated(Repeated::Last).missing(Missing::Skip);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((pairs(&[("a", "2")]), ""));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn query_can_reject_repeated_keys() {
+        let input = "a=1&a=2";
+        let parser = query().repeated(Repeated::Reject);
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingNamed("a key that is not repeated".to_owned(), Found::Text("a".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn decode_keeps_malformed_escapes() {
+        let input = "100%+%zz%4";
+
+        let actual = decode(input);
+
+        let expected = "100% %zz%4";
+        assert_eq!(actual, expected);
+    }
+}
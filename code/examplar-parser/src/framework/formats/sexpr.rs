@@ -0,0 +1,147 @@
+use crate::framework::{Found, Parser, ParseError, any, escaped_transform, map, take_while};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sexp {
+    Atom(String),
+    Number(f64),
+    String(String),
+    List(Vec<Sexp>),
+}
+
+// Dropping nested lists one inside the other would use up the call stack
+// that `sexp` is careful not to, so their items are moved out onto a stack of
+// their own and dropped from there.
+impl Drop for Sexp {
+    fn drop(&mut self) {
+        let mut stack = match self {
+            Sexp::List(items) => std::mem::take(items),
+            _ => return,
+        };
+        while let Some(mut value) = stack.pop() {
+            if let Sexp::List(items) = &mut value {
+                stack.append(items);
+            }
+        }
+    }
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "()\";".contains(c)
+}
+
+// Whitespace and `;` comments running to the end of the line.
+fn skip_trivia(input: &str) -> &str {
+    let mut rem = input.trim_start();
+    while rem.starts_with(';') {
+        rem = rem.find('\n').map_or("", |end| &rem[end..]).trim_start();
+    }
+    rem
+}
+
+fn string<'a>() -> impl Parser<'a, String> {
+    let escape = map(any(|_| true), |c| match c {
+        'n' => '\n',
+        't' => '\t',
+        _ => c,
+    });
+    let text = escaped_transform(any(|c| c != '"' && c != '\\'), '\\', escape);
+    move |input: &'a str| {
+        let (text, rem) = text.parse(&input[1..])?;
+        match rem.strip_prefix('"') {
+            Some(rem) => Ok((text, rem)),
+            None => Err(ParseError::ExpectingCharacter('"', Found::at(rem))),
+        }
+    }
+}
+
+// Numbers are atoms that start with a digit, or a sign and a digit, and read
+// as one; everything else, like `-` or `1+`, stays an atom.
+fn atom(text: &str) -> Sexp {
+    let unsigned = text.strip_prefix(|c| c == '-' || c == '+').unwrap_or(text);
+    match text.parse() {
+        Ok(number) if unsigned.starts_with(|c: char| c.is_ascii_digit()) => Sexp::Number(number),
+        _ => Sexp::Atom(text.to_owned()),
+    }
+}
+
+// Lists are kept on an explicit stack rather than parsed recursively, so
+// nesting depth is limited by memory instead of the call stack.
+pub fn sexp<'a>() -> impl Parser<'a, Sexp> {
+    let string = string();
+    move |input: &'a str| {
+        let mut open: Vec<Vec<Sexp>> = vec![];
+        let mut rem = input;
+        loop {
+            rem = skip_trivia(rem);
+            let (value, rest) = match rem.chars().next() {
+                Some('(') => {
+                    open.push(vec![]);
+                    rem = &rem[1..];
+                    continue;
+                }
+                Some(')') if !open.is_empty() => (Sexp::List(open.pop().expect("an open list")), &rem[1..]),
+                Some('"') => string.parse(rem).map(|(text, rest)| (Sexp::String(text), rest))?,
+                Some(c) if !is_delimiter(c) => map(take_while(|c| !is_delimiter(c)), atom).parse(rem)?,
+                _ if !open.is_empty() => return Err(ParseError::ExpectingCharacter(')', Found::at(rem))),
+                _ => return Err(ParseError::ExpectingNamed("an s-expression".to_owned(), Found::at(rem))),
+            };
+            rem = rest;
+            match open.last_mut() {
+                Some(list) => list.push(value),
+                None => return Ok((value, rem)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn atom(text: &str) -> Sexp {
+        Sexp::Atom(text.to_owned())
+    }
+
+    #[test]
+    fn sexp_parses_nested_lists() {
+        let input = "(define (add x) ; adds one\n  (+ x 1.5 \"a \\\"b\\\"\" -))rest";
+        let parser = sexp();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((Sexp::List(vec![
+            atom("define"),
+            Sexp::List(vec![atom("add"), atom("x")]),
+            Sexp::List(vec![atom("+"), atom("x"), Sexp::Number(1.5), Sexp::String("a \"b\"".to_owned()), atom("-")]),
+        ]), "rest"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn sexp_reports_an_unclosed_list() {
+        let input = "(a (b)";
+        let parser = sexp();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingCharacter(')', Found::EndOfInput));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn sexp_handles_deep_nesting() {
+        let depth = 1_000_000;
+        let input = format!("{}x{}", "(".repeat(depth), ")".repeat(depth));
+        let parser = sexp();
+
+        let (value, rem) = parser.parse(&input).expect("to parse deeply nested lists");
+
+        let mut levels = 0;
+        let mut innermost = &value;
+        while let Sexp::List(items) = innermost {
+            levels += 1;
+            innermost = &items[0];
+        }
+        assert_eq!((levels, innermost, rem), (depth, &atom("x"), ""));
+    }
+}
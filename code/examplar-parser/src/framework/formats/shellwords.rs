@@ -0,0 +1,118 @@
+use crate::framework::{Found, Parser, ParseError, any, character, end, escaped_transform, map, take_while};
+
+// Splits a command line the way a POSIX shell does, without any expansion:
+// single quotes keep everything literally, double quotes only let a
+// backslash escape `$`, `` ` ``, `"`, `\` and a newline, and outside quotes
+// a backslash escapes any character. An escaped newline joins lines.
+
+fn is_separator(c: char) -> bool {
+    c.is_ascii_whitespace()
+}
+
+fn joined_line(c: char) -> String {
+    if c == '\n' { String::new() } else { c.to_string() }
+}
+
+fn unquoted<'a>() -> impl Parser<'a, String> {
+    escaped_transform(any(|c| !is_separator(c) && !"'\"\\".contains(c)), '\\', map(any(|_| true), joined_line))
+}
+
+fn single_quoted<'a>() -> impl Parser<'a, String> {
+    move |input: &'a str| {
+        let (_, rem) = character('\'').parse(input)?;
+        let (text, rem) = take_while(|c| c != '\'').parse(rem)?;
+        let (_, rem) = character('\'').parse(rem)?;
+        Ok((text.to_owned(), rem))
+    }
+}
+
+fn double_quoted<'a>() -> impl Parser<'a, String> {
+    let escape = map(any(|_| true), |c| match c {
+        '$' | '`' | '"' | '\\' => c.to_string(),
+        '\n' => String::new(),
+        _ => format!("\\{}", c),
+    });
+    let text = escaped_transform(any(|c| c != '"' && c != '\\'), '\\', escape);
+    move |input: &'a str| {
+        let (_, rem) = character('"').parse(input)?;
+        let (text, rem) = text.parse(rem)?;
+        let (_, rem) = character('"').parse(rem)?;
+        Ok((text, rem))
+    }
+}
+
+// Adjacent quoted and unquoted parts make up one word, e.g. `a"b c"'d'`. The
+// part is chosen by its first character so an unterminated quote is reported
+// as such rather than as a failed alternative.
+pub fn word<'a>() -> impl Parser<'a, String> {
+    let (single, double, bare) = (single_quoted(), double_quoted(), unquoted());
+    move |input: &'a str| {
+        let mut word = String::new();
+        let mut rem = input;
+        loop {
+            let (part, rest) = match rem.chars().next() {
+                Some('\'') => single.parse(rem)?,
+                Some('"') => double.parse(rem)?,
+                Some(c) if !is_separator(c) => bare.parse(rem)?,
+                _ if rem.len() == input.len() => return Err(ParseError::ExpectingNamed("a word".to_owned(), Found::at(rem))),
+                _ => return Ok((word, rem)),
+            };
+            word.push_str(&part);
+            rem = rest;
+        }
+    }
+}
+
+pub fn words<'a>() -> impl Parser<'a, Vec<String>> {
+    let word = word();
+    move |input: &'a str| {
+        let mut words = vec![];
+        let (_, mut rem) = take_while(is_separator).parse(input)?;
+        while !rem.is_empty() {
+            let (word, rest) = word.parse(rem)?;
+            words.push(word);
+            let (_, rest) = take_while(is_separator).parse(rest)?;
+            rem = rest;
+        }
+        Ok((words, rem))
+    }
+}
+
+pub fn split(line: &str) -> Result<Vec<String>, ParseError> {
+    end(words()).parse(line).map(|(words, _)| words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_honours_quotes_and_escapes() {
+        let input = r#"  echo 'it''s' "a \"b\" \n" c\ d e"f"'g'  "#;
+
+        let actual = split(input);
+
+        let expected = Ok(vec!["echo".to_owned(), "its".to_owned(), r#"a "b" \n"#.to_owned(), "c d".to_owned(), "efg".to_owned()]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn split_keeps_empty_quoted_words() {
+        let input = "a '' \"\"";
+
+        let actual = split(input);
+
+        let expected = Ok(vec!["a".to_owned(), String::new(), String::new()]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn split_rejects_an_unterminated_quote() {
+        let input = "echo 'oops";
+
+        let actual = split(input);
+
+        let expected = Err(ParseError::ExpectingCharacter('\'', Found::EndOfInput));
+        assert_eq!(actual, expected);
+    }
+}
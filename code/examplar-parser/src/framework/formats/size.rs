@@ -0,0 +1,81 @@
+use std::convert::TryFrom;
+
+use crate::framework::{Found, Parser, ParseError, at_least, digit, recognize, take_while};
+use crate::framework::unicode::literal_caseless;
+
+// Suffixes are matched ignoring case: `k`, `kb` and `KB` are SI kilobytes,
+// `KiB` is 1024 bytes, and so on up to exabytes. A bare number or `B` is
+// bytes. Fractions are allowed (`1.5GiB`) and rounded down to whole bytes.
+const PREFIXES: [&str; 6] = ["k", "m", "g", "t", "p", "e"];
+
+fn suffixes() -> Vec<(String, u128)> {
+    let mut suffixes = vec![("b".to_owned(), 1)];
+    for (power, prefix) in (1..).zip(PREFIXES.iter()) {
+        suffixes.push((prefix.to_string(), 1000u128.pow(power)));
+        suffixes.push((format!("{}b", prefix), 1000u128.pow(power)));
+        suffixes.push((format!("{}ib", prefix), 1024u128.pow(power)));
+    }
+    suffixes.sort_by_key(|(suffix, _)| std::cmp::Reverse(suffix.len()));
+    suffixes
+}
+
+pub fn byte_size<'a>() -> impl Parser<'a, u64> {
+    let suffixes = suffixes();
+    move |input: &'a str| {
+        let (whole, rem) = recognize(at_least(1, digit())).parse(input)?;
+        let (fraction, rem) = match rem.strip_prefix('.') {
+            Some(rest) => recognize(at_least(1, digit())).parse(rest)?,
+            None => ("", rem),
+        };
+        let (_, spaced) = take_while(|c| c == ' ').parse(rem)?;
+        let (multiplier, rem) = suffixes.iter()
+            .filter_map(|(suffix, multiplier)| literal_caseless(suffix).parse(spaced).ok().map(|(_, rest)| (*multiplier, rest)))
+            .find(|(_, rest)| !rest.starts_with(char::is_alphabetic))
+            .unwrap_or((1, rem));
+        let text = &input[..input.len() - rem.len()];
+        let overflow = || ParseError::Overflow(Found::Text(text.to_owned()));
+        let whole = whole.parse::<u128>().ok().and_then(|whole| whole.checked_mul(multiplier)).ok_or_else(overflow)?;
+        let fraction = fraction.chars().rev()
+            .fold(0, |part, digit| (part + digit.to_digit(10).expect("a digit") as u128 * multiplier) / 10);
+        let bytes = u64::try_from(whole + fraction).map_err(|_| overflow())?;
+        Ok((bytes, rem))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_size_understands_si_and_binary_suffixes() {
+        let parser = byte_size();
+
+        assert_eq!(parser.parse("512"), Ok((512, "")));
+        assert_eq!(parser.parse("512k"), Ok((512_000, "")));
+        assert_eq!(parser.parse("10MiB"), Ok((10 * 1024 * 1024, "")));
+        assert_eq!(parser.parse("2 gb,"), Ok((2_000_000_000, ",")));
+        assert_eq!(parser.parse("1.5KiB"), Ok((1536, "")));
+    }
+
+    #[test]
+    fn byte_size_leaves_a_suffix_that_is_part_of_a_word() {
+        let input = "3 kilos";
+        let parser = byte_size();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok((3, " kilos"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn byte_size_reports_overflow() {
+        let input = "16EiB";
+        let parser = byte_size();
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::Overflow(Found::Text("16EiB".to_owned())));
+        assert_eq!(actual, expected);
+    }
+}
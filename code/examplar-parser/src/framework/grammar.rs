@@ -0,0 +1,792 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+pub mod analysis;
+
+use super::{Parser, ParseError, ParseResult, Found, SNIPPET_LENGTH, any, at_least, character, end, escaped_transform, literal, many, map, satisfy_named, skip, take_till, take_until_and_consume, take_while};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tree {
+    Node(String, Vec<Tree>),
+    Token(String),
+    Trivia(String),
+}
+
+impl Tree {
+    pub fn text(&self) -> String {
+        match self {
+            Tree::Node(_, children) => children.iter().map(Tree::text).collect(),
+            Tree::Token(text) | Tree::Trivia(text) => text.clone(),
+        }
+    }
+
+    pub fn without_trivia(&self) -> Tree {
+        match self {
+            Tree::Node(name, children) => {
+                let children = children.iter()
+                    .filter(|child| !matches!(child, Tree::Trivia(_)))
+                    .map(Tree::without_trivia)
+                    .collect();
+                Tree::Node(name.clone(), children)
+            }
+            tree => tree.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Literal(String),
+    Range(char, char),
+    AnyCharacter,
+    Rule(String),
+    Not(Box<Expression>),
+    Sequence(Vec<Expression>),
+    Choice(Vec<Expression>),
+    Optional(Box<Expression>),
+    Repeat(Box<Expression>, usize),
+}
+
+// How many rules deep a parse may go before it gives up with
+// `RecursionLimitExceeded` rather than overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grammar {
+    start: String,
+    rules: HashMap<String, Expression>,
+    trivia: Option<String>,
+    max_depth: usize,
+}
+
+impl Grammar {
+    // Left recursion is rejected up front: applying such a rule would apply it
+    // again at the same position without end.
+    pub fn new(definition: &str) -> Result<Self, ParseError> {
+        let (definitions, _) = definitions().parse(definition)?;
+        let start = definitions[0].0.clone();
+        let mut rules = HashMap::new();
+        for (name, expression) in definitions {
+            if rules.contains_key(&name) {
+                return Err(ParseError::DuplicateRule(name));
+            }
+            rules.insert(name, expression);
+        }
+        for expression in rules.values() {
+            if let Some(name) = unknown_rule(expression, &rules) {
+                return Err(ParseError::UnknownRule(name));
+            }
+        }
+        let grammar = Self { start, rules, trivia: None, max_depth: MAX_DEPTH };
+        match analysis::left_recursive_rule(&grammar) {
+            Some(name) => Err(ParseError::LeftRecursion(name)),
+            None => Ok(grammar),
+        }
+    }
+
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    // Skips the trivia rule before every token and keeps what it matched as
+    // `Tree::Trivia`, so the tree reproduces the input exactly. Rules whose
+    // name starts with an uppercase letter are tokens and are matched without
+    // trivia inside them.
+    pub fn with_trivia(mut self, rule: &str) -> Result<Self, ParseError> {
+        if !self.rules.contains_key(rule) {
+            return Err(ParseError::UnknownRule(rule.to_owned()));
+        }
+        self.trivia = Some(rule.to_owned());
+        Ok(self)
+    }
+
+    pub fn start(&self) -> &str {
+        &self.start
+    }
+
+    pub fn expression(&self, rule: &str) -> Option<&Expression> {
+        self.rules.get(rule)
+    }
+
+    pub fn rule<'g>(&'g self, name: &str) -> Result<RuleParser<'g>, ParseError> {
+        if !self.rules.contains_key(name) {
+            return Err(ParseError::UnknownRule(name.to_owned()));
+        }
+        Ok(RuleParser { grammar: self, name: name.to_owned() })
+    }
+
+    // Leading trivia is picked up by the first token; trailing trivia has no
+    // token after it, so it is attached to the end of the root node.
+    fn run<'a>(&self, name: &str, input: &'a str, mut memo: Option<&mut Memo>) -> ParseResult<'a, Tree> {
+        let skip_trivia = self.trivia.is_some() && !is_token(name);
+        let (mut tree, rem) = self.apply(name, input, memo.as_deref_mut(), skip_trivia, 0)?;
+        if !skip_trivia {
+            return Ok((tree, rem));
+        }
+        let mut trailing = vec![];
+        let rem = self.skip_trivia(rem, &mut trailing, memo, 0)?;
+        if let Tree::Node(_, children) = &mut tree {
+            children.append(&mut trailing);
+        }
+        Ok((tree, rem))
+    }
+
+    fn apply<'a>(&self, name: &str, input: &'a str, memo: Option<&mut Memo>, skip_trivia: bool, depth: usize) -> ParseResult<'a, Tree> {
+        if depth >= self.max_depth {
+            return Err(ParseError::RecursionLimitExceeded);
+        }
+        let memo = match memo {
+            Some(memo) => memo,
+            None => {
+                let mut children = vec![];
+                let rem = self.evaluate(&self.rules[name], input, &mut children, None, skip_trivia, depth + 1)?;
+                return Ok((Tree::Node(name.to_owned(), children), rem));
+            }
+        };
+        let offset = memo.length - input.len();
+        let key = (name.to_owned(), offset, skip_trivia);
+        if let Some(entry) = memo.entries.get(&key) {
+            memo.hits += 1;
+            memo.furthest = memo.furthest.max(entry.examined);
+            return entry.result.clone().map(|(tree, consumed)| (tree, &input[consumed..]));
+        }
+        let outer = memo.furthest;
+        memo.furthest = offset;
+        let mut children = vec![];
+        let result = self.evaluate(&self.rules[name], input, &mut children, Some(memo), skip_trivia, depth + 1)
+            .map(|rem| (Tree::Node(name.to_owned(), children), rem));
+        let examined = memo.furthest;
+        // A fatal error ends the whole parse, and whether the depth limit is
+        // hit depends on how the rule was reached, so neither is remembered.
+        if matches!(&result, Err(e) if e.is_fatal()) {
+            return result;
+        }
+        let entry = MemoEntry {
+            result: match &result {
+                Ok((tree, rem)) => Ok((tree.clone(), input.len() - rem.len())),
+                Err(e) => Err(e.clone()),
+            },
+            examined,
+        };
+        memo.entries.insert(key, entry);
+        memo.furthest = outer.max(examined);
+        result
+    }
+
+    fn skip_trivia<'a>(&self, input: &'a str, children: &mut Vec<Tree>, memo: Option<&mut Memo>, depth: usize) -> Result<&'a str, ParseError> {
+        let rule = match &self.trivia {
+            Some(rule) => rule,
+            None => return Ok(input),
+        };
+        let mut ignored = vec![];
+        match self.evaluate(&self.rules[rule], input, &mut ignored, memo, false, depth) {
+            Ok(rem) if rem.len() < input.len() => {
+                children.push(Tree::Trivia(input[..input.len() - rem.len()].to_owned()));
+                Ok(rem)
+            }
+            Err(e) if e.is_fatal() => Err(e),
+            _ => Ok(input),
+        }
+    }
+
+    fn evaluate<'a>(&self, expression: &Expression, input: &'a str, children: &mut Vec<Tree>, mut memo: Option<&mut Memo>, skip_trivia: bool, depth: usize) -> Result<&'a str, ParseError> {
+        match expression {
+            Expression::Literal(_) | Expression::Range(..) | Expression::AnyCharacter => {
+                let mark = children.len();
+                let rem = if skip_trivia { self.skip_trivia(input, children, memo.as_deref_mut(), depth)? } else { input };
+                match terminal(expression, rem, &mut memo) {
+                    Ok((token, rest)) => {
+                        children.push(Tree::Token(token));
+                        Ok(rest)
+                    }
+                    Err(e) => {
+                        children.truncate(mark);
+                        Err(e)
+                    }
+                }
+            }
+
+            Expression::Rule(name) if skip_trivia && is_token(name) => {
+                let mark = children.len();
+                let rem = self.skip_trivia(input, children, memo.as_deref_mut(), depth)?;
+                match self.apply(name, rem, memo, false, depth) {
+                    Ok((tree, rest)) => {
+                        children.push(tree);
+                        Ok(rest)
+                    }
+                    Err(e) => {
+                        children.truncate(mark);
+                        Err(e)
+                    }
+                }
+            }
+
+            Expression::Rule(name) => {
+                let (tree, rem) = self.apply(name, input, memo, skip_trivia, depth)?;
+                children.push(tree);
+                Ok(rem)
+            }
+
+            Expression::Not(inner) => {
+                let mut ignored = vec![];
+                match self.evaluate(inner, input, &mut ignored, memo, skip_trivia, depth) {
+                    Ok(_) => Err(ParseError::UnexpectedFollowingInput(Found::snippet(input, SNIPPET_LENGTH))),
+                    Err(e) if e.is_fatal() => Err(e),
+                    Err(_) => Ok(input),
+                }
+            }
+
+            Expression::Sequence(items) => {
+                let mark = children.len();
+                let mut rem = input;
+                for item in items {
+                    match self.evaluate(item, rem, children, memo.as_deref_mut(), skip_trivia, depth) {
+                        Ok(rest) => rem = rest,
+                        Err(e) => {
+                            children.truncate(mark);
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(rem)
+            }
+
+            Expression::Choice(options) => {
+                let mark = children.len();
+                for option in options {
+                    match self.evaluate(option, input, children, memo.as_deref_mut(), skip_trivia, depth) {
+                        Ok(rem) => return Ok(rem),
+                        Err(e) if e.is_fatal() => return Err(e),
+                        Err(_) => children.truncate(mark),
+                    }
+                }
+                Err(ParseError::ExpectingOneOfToParse(Found::at(input)))
+            }
+
+            Expression::Optional(inner) => {
+                let mark = children.len();
+                match self.evaluate(inner, input, children, memo, skip_trivia, depth) {
+                    Err(e) if e.is_fatal() => Err(e),
+                    Err(_) => {
+                        children.truncate(mark);
+                        Ok(input)
+                    }
+                    attempt => attempt,
+                }
+            }
+
+            Expression::Repeat(inner, minimum) => {
+                let mut rem = input;
+                let mut count = 0;
+                loop {
+                    let mark = children.len();
+                    match self.evaluate(inner, rem, children, memo.as_deref_mut(), skip_trivia, depth) {
+                        Ok(rest) if rest.len() == rem.len() => return Err(ParseError::InfiniteLoopDetected),
+                        Ok(rest) => rem = rest,
+                        Err(e) if e.is_fatal() || count < *minimum => return Err(e),
+                        Err(_) => {
+                            children.truncate(mark);
+                            return Ok(rem);
+                        }
+                    }
+                    count += 1;
+                }
+            }
+        }
+    }
+}
+
+fn is_token(name: &str) -> bool {
+    name.starts_with(char::is_uppercase)
+}
+
+fn terminal<'a>(expression: &Expression, input: &'a str, memo: &mut Option<&mut Memo>) -> ParseResult<'a, String> {
+    match expression {
+        Expression::Literal(text) => {
+            examine(memo, input, text.len());
+            let (matched, rem) = literal(text).parse(input)?;
+            Ok((matched.to_owned(), rem))
+        }
+
+        Expression::Range(low, high) => {
+            examine(memo, input, 1);
+            let name = format!("{:?}..{:?}", low, high);
+            let (c, rem) = satisfy_named(name, |c| *low <= c && c <= *high).parse(input)?;
+            Ok((c.to_string(), rem))
+        }
+
+        _ => {
+            examine(memo, input, 1);
+            let (c, rem) = any(|_| true).parse(input)?;
+            Ok((c.to_string(), rem))
+        }
+    }
+}
+
+impl<'a> Parser<'a, Tree> for Grammar {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Tree> {
+        self.run(&self.start, input, None)
+    }
+}
+
+pub struct RuleParser<'g> {
+    grammar: &'g Grammar,
+    name: String,
+}
+
+impl<'a, 'g> Parser<'a, Tree> for RuleParser<'g> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Tree> {
+        self.grammar.run(&self.name, input, None)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    range: Range<usize>,
+    replacement: String,
+}
+
+impl Edit {
+    pub fn new<S>(range: Range<usize>, replacement: S) -> Self where S: Into<String> {
+        Self { range, replacement: replacement.into() }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Memo {
+    length: usize,
+    furthest: usize,
+    hits: usize,
+    entries: HashMap<(String, usize, bool), MemoEntry>,
+}
+
+#[derive(Debug)]
+struct MemoEntry {
+    result: Result<(Tree, usize), ParseError>,
+    examined: usize,
+}
+
+impl Memo {
+    // Entries that only looked at text before the edit stay put, entries that
+    // start after it move along with the text, everything else is damaged.
+    fn damage(&mut self, edit: &Edit) {
+        let Range { start, end } = edit.range;
+        let inserted = edit.replacement.len();
+        let entries = std::mem::take(&mut self.entries);
+        for ((name, offset, skip_trivia), mut entry) in entries {
+            if entry.examined <= start {
+                self.entries.insert((name, offset, skip_trivia), entry);
+            } else if offset >= end {
+                entry.examined = entry.examined - end + start + inserted;
+                self.entries.insert((name, offset - end + start + inserted, skip_trivia), entry);
+            }
+        }
+    }
+}
+
+fn examine(memo: &mut Option<&mut Memo>, input: &str, length: usize) {
+    if let Some(memo) = memo {
+        let offset = memo.length - input.len();
+        memo.furthest = memo.furthest.max(offset + length);
+    }
+}
+
+pub struct Document<'g> {
+    grammar: &'g Grammar,
+    text: String,
+    memo: Memo,
+}
+
+impl<'g> Document<'g> {
+    pub fn new<S>(grammar: &'g Grammar, text: S) -> Self where S: Into<String> {
+        Self { grammar, text: text.into(), memo: Memo::default() }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn reused(&self) -> usize {
+        self.memo.hits
+    }
+
+    pub fn parse(&mut self) -> Result<Tree, ParseError> {
+        self.memo.length = self.text.len();
+        self.memo.furthest = 0;
+        self.memo.hits = 0;
+        let (tree, rem) = self.grammar.run(&self.grammar.start, &self.text, Some(&mut self.memo))?;
+        if !rem.is_empty() {
+            return Err(ParseError::ExpectingToBeAtEndOfInput(Found::snippet(rem, SNIPPET_LENGTH)));
+        }
+        Ok(tree)
+    }
+
+    // Edits come from outside, e.g. an editor, so a range that is reversed,
+    // runs past the end or splits a character is rejected, leaving the
+    // document as it was.
+    pub fn edit(&mut self, edit: Edit) -> Result<Tree, ParseError> {
+        let Range { start, end } = edit.range;
+        if start > end || end > self.text.len() || !self.text.is_char_boundary(start) || !self.text.is_char_boundary(end) {
+            return Err(ParseError::InvalidEdit(start, end));
+        }
+        self.text.replace_range(edit.range.clone(), &edit.replacement);
+        self.memo.damage(&edit);
+        self.parse()
+    }
+}
+
+fn unknown_rule(expression: &Expression, rules: &HashMap<String, Expression>) -> Option<String> {
+    match expression {
+        Expression::Rule(name) if !rules.contains_key(name) => Some(name.clone()),
+
+        Expression::Sequence(items) | Expression::Choice(items) => {
+            items.iter().find_map(|item| unknown_rule(item, rules))
+        }
+
+        Expression::Not(inner) | Expression::Optional(inner) | Expression::Repeat(inner, _) => unknown_rule(inner, rules),
+
+        _ => None,
+    }
+}
+
+fn definitions<'a>() -> impl Parser<'a, Vec<(String, Expression)>> {
+    end(at_least(1, definition()))
+}
+
+fn definition<'a>() -> impl Parser<'a, (String, Expression)> {
+    crate::sequence_skipping!{ junk();
+        let name = identifier(),
+        let _equals = character('='),
+        let expression = expression(),
+        let _semicolon = character(';')
+        =>
+        (name, expression)
+    }
+}
+
+fn expression<'a>() -> impl Parser<'a, Expression> {
+    move |input: &'a str| {
+        let (first, rem) = terms().parse(input)?;
+        let (mut rest, rem) = many(crate::sequence_skipping!{ junk();
+            let _bar = character('|'),
+            let alternative = terms()
+            =>
+            alternative
+        }).parse(rem)?;
+        if rest.is_empty() {
+            return Ok((first, rem));
+        }
+        rest.insert(0, first);
+        Ok((Expression::Choice(rest), rem))
+    }
+}
+
+fn terms<'a>() -> impl Parser<'a, Expression> {
+    map(at_least(1, |input: &'a str| {
+        let (_, rem) = junk().parse(input)?;
+        item().parse(rem)
+    }), |mut items: Vec<Expression>| {
+        if items.len() == 1 {
+            items.remove(0)
+        } else {
+            Expression::Sequence(items)
+        }
+    })
+}
+
+fn item<'a>() -> impl Parser<'a, Expression> {
+    move |input: &'a str| {
+        if let Ok((_, rem)) = character('!').parse(input) {
+            let (expression, rem) = item().parse(rem)?;
+            return Ok((Expression::Not(Box::new(expression)), rem));
+        }
+        let (expression, rem) = primary().parse(input)?;
+        match rem.chars().next() {
+            Some('*') => Ok((Expression::Repeat(Box::new(expression), 0), &rem[1..])),
+            Some('+') => Ok((Expression::Repeat(Box::new(expression), 1), &rem[1..])),
+            Some('?') => Ok((Expression::Optional(Box::new(expression)), &rem[1..])),
+            _ => Ok((expression, rem)),
+        }
+    }
+}
+
+fn primary<'a>() -> impl Parser<'a, Expression> {
+    move |input: &'a str| {
+        if let Ok((name, rem)) = identifier().parse(input) {
+            return Ok((Expression::Rule(name), rem));
+        }
+        if let Ok((text, rem)) = quoted().parse(input) {
+            return range_or_literal(text, rem);
+        }
+        if let Ok((_, rem)) = character('.').parse(input) {
+            return Ok((Expression::AnyCharacter, rem));
+        }
+        if let Ok((expression, rem)) = group('(', ')').parse(input) {
+            return Ok((expression, rem));
+        }
+        if let Ok((expression, rem)) = group('[', ']').parse(input) {
+            return Ok((Expression::Optional(Box::new(expression)), rem));
+        }
+        if let Ok((expression, rem)) = group('{', '}').parse(input) {
+            return Ok((Expression::Repeat(Box::new(expression), 0), rem));
+        }
+        Err(ParseError::ExpectingNamed("expression".to_owned(), Found::at(input)))
+    }
+}
+
+fn range_or_literal(text: String, input: &str) -> ParseResult<'_, Expression> {
+    let range = crate::sequence_skipping!{ junk();
+        let _dots = literal(".."),
+        let high = quoted()
+        =>
+        high
+    };
+    match range.parse(input) {
+        Ok((high, rem)) => {
+            let mut low_characters = text.chars();
+            let mut high_characters = high.chars();
+            match (low_characters.next(), low_characters.next(), high_characters.next(), high_characters.next()) {
+                (Some(low), None, Some(high), None) => Ok((Expression::Range(low, high), rem)),
+                _ => Err(ParseError::ExpectingNamed("single character range".to_owned(), Found::Text(text))),
+            }
+        }
+
+        Err(_) => Ok((Expression::Literal(text), input)),
+    }
+}
+
+fn group<'a>(open: char, close: char) -> impl Parser<'a, Expression> {
+    crate::move_sequence_skipping!{ junk();
+        let _open = character(open),
+        let expression = expression(),
+        let _close = character(close)
+        =>
+        expression
+    }
+}
+
+fn identifier<'a>() -> impl Parser<'a, String> {
+    crate::sequence!{
+        let first = satisfy_named("identifier", |c| c.is_alphabetic() || c == '_'),
+        let rest = take_while(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        =>
+        format!("{}{}", first, rest)
+    }
+}
+
+fn quoted<'a>() -> impl Parser<'a, String> {
+    move |input: &'a str| {
+        let (quote, rem) = satisfy_named("quote", |c| c == '"' || c == '\'').parse(input)?;
+        let (content, rem) = escaped_transform(
+            take_till(|c| c == quote || c == '\\'),
+            '\\',
+            map(any(|_| true), unescape)).parse(rem)?;
+        let (_, rem) = character(quote).parse(rem)?;
+        Ok((content, rem))
+    }
+}
+
+fn unescape(c: char) -> char {
+    match c {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        _ => c,
+    }
+}
+
+fn junk<'a>() -> impl Parser<'a, ()> {
+    skip(many(|input: &'a str| {
+        match satisfy_named("whitespace", char::is_whitespace).parse(input) {
+            Ok((_, rem)) => Ok(((), rem)),
+            Err(_) => comment().parse(input),
+        }
+    }))
+}
+
+fn comment<'a>() -> impl Parser<'a, ()> {
+    crate::sequence!{
+        let _open = literal("(*"),
+        let _content = take_until_and_consume("*)")
+        =>
+        ()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAIRS: &str = r#"
+        (* key=value pairs *)
+        pair = key "=" value ;
+        key = letter { letter } ;
+        letter = "a" .. "z" ;
+        value = digit+ | "none" ;
+        digit = "0" .. "9" ;
+    "#;
+
+    #[test]
+    fn grammar_parses_its_start_rule() {
+        let grammar = Grammar::new(PAIRS).expect("to parse a grammar");
+
+        let (tree, rem) = grammar.parse("ab=12;").expect("to parse a pair");
+
+        assert_eq!(tree.text(), "ab=12");
+        assert_eq!(rem, ";");
+        match tree {
+            Tree::Node(name, children) => {
+                assert_eq!(name, "pair");
+                assert_eq!(children[1], Tree::Token("=".to_owned()));
+            }
+            _ => panic!("expected a rule node"),
+        }
+    }
+
+    #[test]
+    fn grammar_rule_can_be_used_as_a_parser() {
+        let grammar = Grammar::new(PAIRS).expect("to parse a grammar");
+        let parser = grammar.rule("value").expect("value to be a rule");
+
+        let actual = parser.parse("none");
+
+        let expected = Ok((Tree::Node("value".to_owned(), vec![Tree::Token("none".to_owned())]), ""));
+        assert_eq!(actual, expected);
+    }
+
+    const STATEMENTS: &str = r#"
+        program = statement* ;
+        statement = name "=" digits ";" ;
+        name = ("a" .. "z")+ ;
+        digits = ("0" .. "9")+ ;
+    "#;
+
+    #[test]
+    fn document_reuses_undamaged_rules_after_an_edit() {
+        let grammar = Grammar::new(STATEMENTS).expect("to parse a grammar");
+        let mut document = Document::new(&grammar, "a=1;b=2;c=3;");
+        document.parse().expect("to parse the document");
+
+        let actual = document.edit(Edit::new(6..7, "42")).expect("to reparse the document");
+
+        let (expected, _) = grammar.parse("a=1;b=42;c=3;").expect("to parse from scratch");
+        assert_eq!(document.text(), "a=1;b=42;c=3;");
+        assert_eq!(actual, expected);
+        assert_eq!(document.reused(), 4);
+    }
+
+    #[test]
+    fn document_reports_errors_introduced_by_an_edit() {
+        let grammar = Grammar::new(STATEMENTS).expect("to parse a grammar");
+        let mut document = Document::new(&grammar, "a=1;b=2;");
+        document.parse().expect("to parse the document");
+
+        let actual = document.edit(Edit::new(7..8, ""));
+
+        let expected = Err(ParseError::ExpectingToBeAtEndOfInput(Found::Text("b=2".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn document_rejects_edits_outside_the_text() {
+        let grammar = Grammar::new(STATEMENTS).expect("to parse a grammar");
+        let mut document = Document::new(&grammar, "é=1;");
+        let inputs = [Edit::new(3..9, ""), Edit::new(1..2, "x"), Edit::new(Range { start: 3, end: 2 }, "")];
+
+        let actual: Vec<_> = inputs.iter().map(|edit| document.edit(edit.clone())).collect();
+
+        let expected = vec![Err(ParseError::InvalidEdit(3, 9)), Err(ParseError::InvalidEdit(1, 2)), Err(ParseError::InvalidEdit(3, 2))];
+        assert_eq!(actual, expected);
+        assert_eq!(document.text(), "é=1;");
+    }
+
+    #[test]
+    fn grammar_reports_unknown_rules() {
+        let actual = Grammar::new("start = missing ;");
+
+        let expected = Err(ParseError::UnknownRule("missing".to_owned()));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn grammar_rejects_duplicate_rules() {
+        let actual = Grammar::new("start = a ; a = \"x\" ; a = \"y\" ;");
+
+        let expected = Err(ParseError::DuplicateRule("a".to_owned()));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn grammar_rejects_left_recursion() {
+        let inputs = ["a = a \"x\" | \"x\" ;", "a = b \"x\" ; b = [\"y\"] a ;", "a = !a \"x\" ;"];
+
+        let actual: Vec<_> = inputs.iter().map(|input| Grammar::new(input)).collect();
+
+        let expected = vec![
+            Err(ParseError::LeftRecursion("a".to_owned())),
+            Err(ParseError::LeftRecursion("a".to_owned())),
+            Err(ParseError::LeftRecursion("a".to_owned())),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn grammar_stops_at_its_depth_limit() {
+        let grammar = Grammar::new("nested = \"(\" [nested] \")\" ;").expect("to parse a grammar");
+        let input = format!("{}{}", "(".repeat(10_000), ")".repeat(10_000));
+
+        let actual = grammar.parse(&input);
+
+        let expected = Err(ParseError::RecursionLimitExceeded);
+        assert_eq!(actual, expected);
+        assert!(grammar.with_max_depth(8).parse("((()))").is_ok());
+    }
+
+    #[test]
+    fn grammar_reports_parse_failures() {
+        let grammar = Grammar::new(PAIRS).expect("to parse a grammar");
+
+        let actual = grammar.parse("AB=1");
+
+        let expected = Err(ParseError::ExpectingNamed("'a'..'z'".to_owned(), Found::Character('A')));
+        assert_eq!(actual, expected);
+    }
+
+    const SUMS: &str = r##"
+        sum = Number ("+" Number)* ;
+        Number = ("0" .. "9")+ ;
+        ws = (" " | "\n" | Comment)* ;
+        Comment = "#" (!"\n" .)* ;
+    "##;
+
+    #[test]
+    fn trivia_grammar_reproduces_its_input() {
+        let grammar = Grammar::new(SUMS).and_then(|g| g.with_trivia("ws")).expect("to parse a grammar");
+        let input = " 1 + 23 # twenty three\n+4\n";
+
+        let (tree, rem) = grammar.parse(input).expect("to parse a sum");
+
+        assert_eq!(rem, "");
+        assert_eq!(tree.text(), input);
+        assert_eq!(tree.without_trivia().text(), "1+23+4");
+    }
+
+    #[test]
+    fn trivia_is_not_skipped_inside_tokens() {
+        let grammar = Grammar::new(SUMS).and_then(|g| g.with_trivia("ws")).expect("to parse a grammar");
+
+        let (tree, rem) = grammar.parse("1 2").expect("to parse a sum");
+
+        assert_eq!(tree.text(), "1 ");
+        assert_eq!(rem, "2");
+    }
+
+    #[test]
+    fn trivia_must_name_a_rule() {
+        let actual = Grammar::new(SUMS).and_then(|g| g.with_trivia("spaces"));
+
+        let expected = Err(ParseError::UnknownRule("spaces".to_owned()));
+        assert_eq!(actual, expected);
+    }
+}
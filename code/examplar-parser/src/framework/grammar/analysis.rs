@@ -0,0 +1,388 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use super::{Expression, Grammar};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    UnreachableRule(String),
+    NullableRepetition(String),
+    ShadowedBranch { rule: String, branch: usize, by: usize },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnreachableRule(rule) => write!(f, "rule {:?} is never used", rule),
+            Warning::NullableRepetition(rule) => write!(f, "rule {:?} repeats something that can match nothing", rule),
+            Warning::ShadowedBranch { rule, branch, by } => {
+                write!(f, "in rule {:?}, alternative {} can never match because alternative {} matches first", rule, branch + 1, by + 1)
+            }
+        }
+    }
+}
+
+// Rules that can succeed without consuming input, found by iterating until
+// nothing changes since rules can refer to each other in cycles.
+pub fn nullable_rules(grammar: &Grammar) -> HashSet<String> {
+    let mut nullable = HashSet::new();
+    loop {
+        let found: Vec<&String> = grammar.rules.iter()
+            .filter(|(name, expression)| !nullable.contains(*name) && is_nullable(expression, &nullable))
+            .map(|(name, _)| name)
+            .collect();
+        if found.is_empty() {
+            return nullable;
+        }
+        nullable.extend(found.into_iter().cloned());
+    }
+}
+
+pub fn is_nullable(expression: &Expression, nullable: &HashSet<String>) -> bool {
+    match expression {
+        Expression::Literal(text) => text.is_empty(),
+        Expression::Range(..) | Expression::AnyCharacter => false,
+        Expression::Rule(name) => nullable.contains(name),
+        Expression::Not(_) | Expression::Optional(_) => true,
+        Expression::Sequence(items) => items.iter().all(|item| is_nullable(item, nullable)),
+        Expression::Choice(options) => options.iter().any(|option| is_nullable(option, nullable)),
+        Expression::Repeat(inner, minimum) => *minimum == 0 || is_nullable(inner, nullable),
+    }
+}
+
+// Problems that would otherwise only show up when parsing: repetitions that
+// would stop with `InfiniteLoopDetected`, alternatives that an earlier one
+// always wins over (choices are ordered, so a later one is never tried), and
+// rules that neither the start rule nor the trivia rule lead to. Warnings come
+// out sorted by rule name.
+pub fn validate(grammar: &Grammar) -> Vec<Warning> {
+    let nullable = nullable_rules(grammar);
+    let reachable = reachable_rules(grammar);
+    let mut names: Vec<&String> = grammar.rules.keys().collect();
+    names.sort();
+    let mut warnings = vec![];
+    for name in names {
+        if !reachable.contains(name.as_str()) {
+            warnings.push(Warning::UnreachableRule(name.clone()));
+        }
+        check(name, &grammar.rules[name], &nullable, &mut warnings);
+    }
+    warnings
+}
+
+fn check(rule: &str, expression: &Expression, nullable: &HashSet<String>, warnings: &mut Vec<Warning>) {
+    match expression {
+        Expression::Repeat(inner, _) => {
+            if is_nullable(inner, nullable) {
+                warnings.push(Warning::NullableRepetition(rule.to_owned()));
+            }
+            check(rule, inner, nullable, warnings);
+        }
+
+        Expression::Choice(options) => {
+            for (branch, option) in options.iter().enumerate() {
+                if let Some(by) = options[..branch].iter().position(|earlier| shadows(earlier, option)) {
+                    warnings.push(Warning::ShadowedBranch { rule: rule.to_owned(), branch, by });
+                }
+                check(rule, option, nullable, warnings);
+            }
+        }
+
+        Expression::Sequence(items) => items.iter().for_each(|item| check(rule, item, nullable, warnings)),
+
+        Expression::Not(inner) | Expression::Optional(inner) => check(rule, inner, nullable, warnings),
+
+        _ => {}
+    }
+}
+
+// Only the cases that can be decided without running the grammar: an earlier
+// alternative that always succeeds, one that is the same, or a literal that
+// is a prefix of every match of the later one.
+fn shadows(earlier: &Expression, later: &Expression) -> bool {
+    if earlier == later || always_succeeds(earlier) {
+        return true;
+    }
+    match (earlier, literal_prefix(later)) {
+        (Expression::Literal(prefix), Some(text)) => text.starts_with(prefix.as_str()),
+        _ => false,
+    }
+}
+
+// Rules are not followed, so this can miss a rule that always succeeds.
+fn always_succeeds(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal(text) => text.is_empty(),
+        Expression::Optional(_) | Expression::Repeat(_, 0) => true,
+        Expression::Sequence(items) => items.iter().all(always_succeeds),
+        Expression::Choice(options) => options.iter().any(always_succeeds),
+        _ => false,
+    }
+}
+
+fn literal_prefix(expression: &Expression) -> Option<&str> {
+    match expression {
+        Expression::Literal(text) => Some(text),
+        Expression::Sequence(items) => items.first().and_then(literal_prefix),
+        _ => None,
+    }
+}
+
+fn reachable_rules(grammar: &Grammar) -> BTreeSet<&str> {
+    let mut reachable = BTreeSet::new();
+    let mut pending: Vec<&str> = vec![&grammar.start];
+    pending.extend(grammar.trivia.as_deref());
+    while let Some(name) = pending.pop() {
+        if reachable.insert(name) {
+            referenced_rules(&grammar.rules[name], &mut pending);
+        }
+    }
+    reachable
+}
+
+fn referenced_rules<'g>(expression: &'g Expression, rules: &mut Vec<&'g str>) {
+    match expression {
+        Expression::Rule(name) => rules.push(name),
+        Expression::Sequence(items) | Expression::Choice(items) => items.iter().for_each(|item| referenced_rules(item, rules)),
+        Expression::Not(inner) | Expression::Optional(inner) | Expression::Repeat(inner, _) => referenced_rules(inner, rules),
+        _ => {}
+    }
+}
+
+// What can come next in the input. Literals only contribute their first
+// character, and `End` stands for the end of the input in FOLLOW sets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Terminal {
+    Character(char),
+    Range(char, char),
+    AnyCharacter,
+    End,
+}
+
+impl Display for Terminal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Terminal::Character(c) => write!(f, "{:?}", c),
+            Terminal::Range(low, high) => write!(f, "{:?}..{:?}", low, high),
+            Terminal::AnyCharacter => write!(f, "any character"),
+            Terminal::End => write!(f, "end of input"),
+        }
+    }
+}
+
+pub type Sets = HashMap<String, BTreeSet<Terminal>>;
+
+// The trivia rule is not taken into account, so with trivia these are the
+// sets of the tokens themselves.
+pub fn first_sets(grammar: &Grammar) -> Sets {
+    let nullable = nullable_rules(grammar);
+    let mut sets: Sets = grammar.rules.keys().map(|name| (name.clone(), BTreeSet::new())).collect();
+    loop {
+        let mut changed = false;
+        for (name, expression) in &grammar.rules {
+            let first = first(expression, &sets, &nullable);
+            let set = sets.get_mut(name).expect("a set for every rule");
+            let before = set.len();
+            set.extend(first);
+            changed |= set.len() != before;
+        }
+        if !changed {
+            return sets;
+        }
+    }
+}
+
+pub fn first(expression: &Expression, sets: &Sets, nullable: &HashSet<String>) -> BTreeSet<Terminal> {
+    match expression {
+        Expression::Literal(text) => text.chars().next().map(Terminal::Character).into_iter().collect(),
+        Expression::Range(low, high) => Some(Terminal::Range(*low, *high)).into_iter().collect(),
+        Expression::AnyCharacter => Some(Terminal::AnyCharacter).into_iter().collect(),
+        Expression::Rule(name) => sets[name].clone(),
+        Expression::Not(_) => BTreeSet::new(),
+        Expression::Sequence(items) => {
+            let mut set = BTreeSet::new();
+            for item in items {
+                set.extend(first(item, sets, nullable));
+                if !is_nullable(item, nullable) {
+                    break;
+                }
+            }
+            set
+        }
+        Expression::Choice(options) => options.iter().flat_map(|option| first(option, sets, nullable)).collect(),
+        Expression::Optional(inner) | Expression::Repeat(inner, _) => first(inner, sets, nullable),
+    }
+}
+
+// The walk `first` does, collecting the rules that can be applied at the
+// position an expression starts at instead of the terminals. A negative
+// lookahead consumes nothing but still applies its rule there.
+fn leading_rules<'g>(expression: &'g Expression, nullable: &HashSet<String>, rules: &mut Vec<&'g str>) {
+    match expression {
+        Expression::Rule(name) => rules.push(name),
+        Expression::Sequence(items) => {
+            for item in items {
+                leading_rules(item, nullable, rules);
+                if !is_nullable(item, nullable) {
+                    break;
+                }
+            }
+        }
+        Expression::Choice(options) => options.iter().for_each(|option| leading_rules(option, nullable, rules)),
+        Expression::Not(inner) | Expression::Optional(inner) | Expression::Repeat(inner, _) => leading_rules(inner, nullable, rules),
+        Expression::Literal(_) | Expression::Range(..) | Expression::AnyCharacter => {}
+    }
+}
+
+// A rule that can get back to itself without consuming anything, directly or
+// through other rules. The first such rule by name is reported.
+pub fn left_recursive_rule(grammar: &Grammar) -> Option<String> {
+    let nullable = nullable_rules(grammar);
+    let leading: HashMap<&str, Vec<&str>> = grammar.rules.iter()
+        .map(|(name, expression)| {
+            let mut rules = vec![];
+            leading_rules(expression, &nullable, &mut rules);
+            (name.as_str(), rules)
+        })
+        .collect();
+    let mut names: Vec<&str> = leading.keys().copied().collect();
+    names.sort_unstable();
+    names.into_iter().find(|name| {
+        let mut seen = HashSet::new();
+        let mut pending = leading[name].clone();
+        while let Some(rule) = pending.pop() {
+            if rule == *name {
+                return true;
+            }
+            if seen.insert(rule) {
+                pending.extend(leading.get(rule).into_iter().flatten());
+            }
+        }
+        false
+    }).map(str::to_owned)
+}
+
+pub fn follow_sets(grammar: &Grammar) -> Sets {
+    let nullable = nullable_rules(grammar);
+    let first = first_sets(grammar);
+    let mut sets: Sets = grammar.rules.keys().map(|name| (name.clone(), BTreeSet::new())).collect();
+    sets.get_mut(&grammar.start).expect("a set for the start rule").insert(Terminal::End);
+    loop {
+        let mut found: Vec<(String, BTreeSet<Terminal>)> = vec![];
+        for (name, expression) in &grammar.rules {
+            follow(expression, sets[name].clone(), &first, &nullable, &mut found);
+        }
+        let mut changed = false;
+        for (name, terminals) in found {
+            let set = sets.get_mut(&name).expect("a set for every rule");
+            let before = set.len();
+            set.extend(terminals);
+            changed |= set.len() != before;
+        }
+        if !changed {
+            return sets;
+        }
+    }
+}
+
+// `after` is what can follow `expression` itself; what each rule inside it
+// can be followed by is collected into `found`. Lookahead consumes nothing,
+// so rules inside `!` are not followed by anything in particular.
+fn follow(expression: &Expression, after: BTreeSet<Terminal>, first_sets: &Sets, nullable: &HashSet<String>, found: &mut Vec<(String, BTreeSet<Terminal>)>) {
+    match expression {
+        Expression::Rule(name) => found.push((name.clone(), after)),
+        Expression::Sequence(items) => {
+            let mut after = after;
+            for item in items.iter().rev() {
+                follow(item, after.clone(), first_sets, nullable, found);
+                let mut next = first(item, first_sets, nullable);
+                if is_nullable(item, nullable) {
+                    next.extend(after);
+                }
+                after = next;
+            }
+        }
+        Expression::Choice(options) => options.iter().for_each(|option| follow(option, after.clone(), first_sets, nullable, found)),
+        Expression::Optional(inner) => follow(inner, after, first_sets, nullable, found),
+        Expression::Repeat(inner, _) => {
+            let mut after = after;
+            after.extend(first(inner, first_sets, nullable));
+            follow(inner, after, first_sets, nullable, found);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nullable_rules_follow_references() {
+        let grammar = Grammar::new(r#"
+            list = items ";" ;
+            items = item* ;
+            item = maybe maybe ;
+            maybe = "x"? ;
+        "#).expect("to parse a grammar");
+
+        let actual: BTreeSet<String> = nullable_rules(&grammar).into_iter().collect();
+
+        let expected = ["item", "items", "maybe"].iter().map(|name| name.to_string()).collect();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn validate_reports_likely_mistakes() {
+        let grammar = Grammar::new(r#"
+            start = ("a" | "ab" | "b"? | "c") blanks ;
+            blanks = [" "]* ;
+            unused = "u" ;
+        "#).expect("to parse a grammar");
+
+        let actual = validate(&grammar);
+
+        let expected = vec![
+            Warning::NullableRepetition("blanks".to_owned()),
+            Warning::ShadowedBranch { rule: "start".to_owned(), branch: 1, by: 0 },
+            Warning::ShadowedBranch { rule: "start".to_owned(), branch: 3, by: 2 },
+            Warning::UnreachableRule("unused".to_owned()),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    const EXPRESSIONS: &str = r#"
+        sum = product { "+" product } ;
+        product = atom [ "*" product ] ;
+        atom = digit+ | "(" sum ")" ;
+        digit = "0" .. "9" ;
+    "#;
+
+    fn terminals(terminals: &[Terminal]) -> BTreeSet<Terminal> {
+        terminals.iter().copied().collect()
+    }
+
+    #[test]
+    fn first_sets_look_through_rules() {
+        let grammar = Grammar::new(EXPRESSIONS).expect("to parse a grammar");
+
+        let actual = first_sets(&grammar);
+
+        let expected = terminals(&[Terminal::Character('('), Terminal::Range('0', '9')]);
+        assert_eq!(actual["sum"], expected);
+        assert_eq!(actual["digit"], terminals(&[Terminal::Range('0', '9')]));
+    }
+
+    #[test]
+    fn follow_sets_include_what_follows_the_enclosing_rule() {
+        let grammar = Grammar::new(EXPRESSIONS).expect("to parse a grammar");
+
+        let actual = follow_sets(&grammar);
+
+        assert_eq!(actual["sum"], terminals(&[Terminal::Character(')'), Terminal::End]));
+        assert_eq!(actual["product"], terminals(&[Terminal::Character(')'), Terminal::Character('+'), Terminal::End]));
+        assert_eq!(actual["digit"], terminals(&[
+            Terminal::Character(')'), Terminal::Character('*'), Terminal::Character('+'), Terminal::Range('0', '9'), Terminal::End,
+        ]));
+    }
+}
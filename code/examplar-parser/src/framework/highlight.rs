@@ -0,0 +1,89 @@
+use super::{BoxedParser, Parser, boxed, skip};
+use super::ast::Span;
+
+// Splits input into classified tokens for syntax highlighting. Rules are
+// tried in the order they were added at each position and the first one that
+// consumes something wins; where none does, one character is skipped and
+// left unclassified, so input that does not parse still gets highlighted up
+// to its end.
+pub struct Classifier<'p, 'a, K> {
+    rules: Vec<(K, BoxedParser<'p, 'a, ()>)>,
+}
+
+impl<'p, 'a, K> Classifier<'p, 'a, K> where K: Clone {
+    pub fn new() -> Self {
+        Self { rules: vec![] }
+    }
+
+    pub fn rule<T, P>(mut self, kind: K, parser: P) -> Self where 'a: 'p, T: 'a, P: Parser<'a, T> + Sized + 'p {
+        self.rules.push((kind, boxed(skip(parser))));
+        self
+    }
+
+    pub fn classify(&self, input: &'a str) -> Vec<(Span, K)> {
+        let mut tokens = vec![];
+        let mut rem = input;
+        while let Some(c) = rem.chars().next() {
+            let matched = self.rules.iter().find_map(|(kind, parser)| match parser.parse(rem) {
+                Ok((_, rest)) if rest.len() < rem.len() => Some((kind, rest)),
+                _ => None,
+            });
+            let start = input.len() - rem.len();
+            rem = match matched {
+                Some((kind, rest)) => {
+                    tokens.push((Span::new(start, input.len() - rest.len()), kind.clone()));
+                    rest
+                }
+                None => &rem[c.len_utf8()..],
+            };
+        }
+        tokens
+    }
+}
+
+impl<'p, 'a, K> Default for Classifier<'p, 'a, K> where K: Clone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn classifier<'p, 'a, K>() -> Classifier<'p, 'a, K> where K: Clone {
+    Classifier::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{any, at_least, digit, keywords, literal, take_until_and_consume};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Kind {
+        Keyword,
+        Identifier,
+        Number,
+        String,
+    }
+
+    #[test]
+    fn classify_keeps_going_past_input_it_cannot_classify() {
+        let input = "let x = 12 @ \"unterminated";
+        let parser = classifier()
+            .rule(Kind::Keyword, keywords(["let"]))
+            .rule(Kind::Identifier, at_least(1, any(char::is_alphabetic)))
+            .rule(Kind::Number, at_least(1, digit()))
+            .rule(Kind::String, move |input: &'static str| {
+                let (_, rem) = literal("\"").parse(input)?;
+                take_until_and_consume("\"").parse(rem)
+            });
+
+        let actual = parser.classify(input);
+
+        let expected = vec![
+            (Span::new(0, 3), Kind::Keyword),
+            (Span::new(4, 5), Kind::Identifier),
+            (Span::new(8, 10), Kind::Number),
+            (Span::new(14, 26), Kind::Identifier),
+        ];
+        assert_eq!(actual, expected);
+    }
+}
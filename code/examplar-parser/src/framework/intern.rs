@@ -0,0 +1,89 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use super::Parser;
+
+// Implemented by anything that can hand out a cheap id for a piece of text,
+// so `interned` works with an interner the caller already has.
+pub trait Intern {
+    type Symbol;
+
+    fn intern(&self, text: &str) -> Self::Symbol;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+#[derive(Debug, Default)]
+pub struct Interner {
+    symbols: RefCell<HashMap<String, Symbol>>,
+    texts: RefCell<Vec<String>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.texts.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> Option<String> {
+        self.texts.borrow().get(symbol.0 as usize).cloned()
+    }
+}
+
+impl Intern for Interner {
+    type Symbol = Symbol;
+
+    fn intern(&self, text: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.borrow().get(text) {
+            return *symbol;
+        }
+        let mut texts = self.texts.borrow_mut();
+        let symbol = Symbol(texts.len() as u32);
+        texts.push(text.to_owned());
+        self.symbols.borrow_mut().insert(text.to_owned(), symbol);
+        symbol
+    }
+}
+
+pub fn interned<'a, 'i, I, P>(interner: &'i I, parser: P) -> impl Parser<'a, I::Symbol> + 'i where I: Intern, P: Parser<'a, &'a str> + Sized + 'i {
+    move |input: &'a str| {
+        let (text, rem) = parser.parse(input)?;
+        Ok((interner.intern(text), rem))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{any, at_least, many, recognize, spaces};
+
+    fn word<'a, 'i>(interner: &'i Interner) -> impl Parser<'a, Symbol> + 'i {
+        interned(interner, move |input: &'a str| {
+            let (text, rem) = recognize(at_least(1, any(char::is_alphabetic))).parse(input)?;
+            let (_, rem) = spaces().parse(rem)?;
+            Ok((text, rem))
+        })
+    }
+
+    #[test]
+    fn interned_gives_repeated_text_the_same_symbol() {
+        let input = "let x let y";
+        let interner = Interner::new();
+        let parser = many(word(&interner));
+
+        let (symbols, _) = parser.parse(input).expect("to parse words");
+
+        assert_eq!(symbols[0], symbols[2]);
+        assert_ne!(symbols[1], symbols[3]);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(interner.resolve(symbols[2]), Some("let".to_owned()));
+    }
+}
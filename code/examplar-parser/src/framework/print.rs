@@ -0,0 +1,230 @@
+use std::marker::PhantomData;
+
+use super::{Found, Parser, ParseError, ParseResult, take_while};
+
+// The other half of `Parser`: writes a value back out as text the parser
+// would accept. The builders in this module implement both, so one
+// description serves to read a file and to write it again.
+pub trait Print<T> {
+    fn print(&self, value: &T, output: &mut String);
+
+    fn printed(&self, value: &T) -> String {
+        let mut output = String::new();
+        self.print(value, &mut output);
+        output
+    }
+}
+
+pub struct Literal<'p>(&'p str);
+
+impl<'a, 'p> Parser<'a, ()> for Literal<'p> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, ()> {
+        super::literal(self.0).parse(input).map(|(_, rem)| ((), rem))
+    }
+}
+
+impl<'p> Print<()> for Literal<'p> {
+    fn print(&self, _: &(), output: &mut String) {
+        output.push_str(self.0);
+    }
+}
+
+pub fn literal(text: &str) -> Literal<'_> {
+    Literal(text)
+}
+
+// A non-empty run of matching characters. Printing writes the value as is,
+// so it is up to the caller to only print values the parser would read.
+pub struct Word<F> {
+    predicate: F,
+}
+
+impl<'a, F> Parser<'a, String> for Word<F> where F: Fn(char) -> bool {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, String> {
+        match take_while(&self.predicate).parse(input)? {
+            ("", _) => Err(ParseError::ExpectingPredicate(Found::at(input))),
+            (word, rem) => Ok((word.to_owned(), rem)),
+        }
+    }
+}
+
+impl<F> Print<String> for Word<F> {
+    fn print(&self, value: &String, output: &mut String) {
+        output.push_str(value);
+    }
+}
+
+pub fn word<F>(predicate: F) -> Word<F> where F: Fn(char) -> bool {
+    Word { predicate }
+}
+
+pub struct Pair<P, Q>(P, Q);
+
+impl<'a, A, B, P, Q> Parser<'a, (A, B)> for Pair<P, Q> where P: Parser<'a, A>, Q: Parser<'a, B> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, (A, B)> {
+        let (first, rem) = self.0.parse(input)?;
+        let (second, rem) = self.1.parse(rem)?;
+        Ok(((first, second), rem))
+    }
+}
+
+impl<A, B, P, Q> Print<(A, B)> for Pair<P, Q> where P: Print<A>, Q: Print<B> {
+    fn print(&self, (first, second): &(A, B), output: &mut String) {
+        self.0.print(first, output);
+        self.1.print(second, output);
+    }
+}
+
+pub fn pair<P, Q>(first: P, second: Q) -> Pair<P, Q> {
+    Pair(first, second)
+}
+
+pub struct Preceded<P, Q>(P, Q);
+
+impl<'a, T, P, Q> Parser<'a, T> for Preceded<P, Q> where P: Parser<'a, ()>, Q: Parser<'a, T> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        let (_, rem) = self.0.parse(input)?;
+        self.1.parse(rem)
+    }
+}
+
+impl<T, P, Q> Print<T> for Preceded<P, Q> where P: Print<()>, Q: Print<T> {
+    fn print(&self, value: &T, output: &mut String) {
+        self.0.print(&(), output);
+        self.1.print(value, output);
+    }
+}
+
+pub fn preceded<P, Q>(prefix: P, parser: Q) -> Preceded<P, Q> {
+    Preceded(prefix, parser)
+}
+
+pub struct Terminated<P, Q>(P, Q);
+
+impl<'a, T, P, Q> Parser<'a, T> for Terminated<P, Q> where P: Parser<'a, T>, Q: Parser<'a, ()> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, T> {
+        let (result, rem) = self.0.parse(input)?;
+        let (_, rem) = self.1.parse(rem)?;
+        Ok((result, rem))
+    }
+}
+
+impl<T, P, Q> Print<T> for Terminated<P, Q> where P: Print<T>, Q: Print<()> {
+    fn print(&self, value: &T, output: &mut String) {
+        self.0.print(value, output);
+        self.1.print(&(), output);
+    }
+}
+
+pub fn terminated<P, Q>(parser: P, suffix: Q) -> Terminated<P, Q> {
+    Terminated(parser, suffix)
+}
+
+// Zero or more items with a separator between them; a separator that is not
+// followed by an item is left unconsumed.
+pub struct Separated<P, S> {
+    item: P,
+    separator: S,
+}
+
+impl<'a, T, P, S> Parser<'a, Vec<T>> for Separated<P, S> where P: Parser<'a, T>, S: Parser<'a, ()> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, Vec<T>> {
+        let mut items = vec![];
+        let mut rem = match self.item.parse(input) {
+            Ok((item, rem)) => {
+                items.push(item);
+                rem
+            }
+            Err(_) => return Ok((items, input)),
+        };
+        while let Ok((item, rest)) = self.separator.parse(rem).and_then(|(_, rest)| self.item.parse(rest)) {
+            items.push(item);
+            rem = rest;
+        }
+        Ok((items, rem))
+    }
+}
+
+impl<T, P, S> Print<Vec<T>> for Separated<P, S> where P: Print<T>, S: Print<()> {
+    fn print(&self, items: &Vec<T>, output: &mut String) {
+        for (index, item) in items.iter().enumerate() {
+            if index > 0 {
+                self.separator.print(&(), output);
+            }
+            self.item.print(item, output);
+        }
+    }
+}
+
+pub fn separated<P, S>(item: P, separator: S) -> Separated<P, S> {
+    Separated { item, separator }
+}
+
+// Converts in both directions, so the value printed is turned back into what
+// the wrapped description prints.
+pub struct Map<P, F, G, I> {
+    syntax: P,
+    into: F,
+    from: G,
+    inner: PhantomData<I>,
+}
+
+impl<'a, I, O, P, F, G> Parser<'a, O> for Map<P, F, G, I> where P: Parser<'a, I>, F: Fn(I) -> O {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, O> {
+        self.syntax.parse(input).map(|(result, rem)| ((self.into)(result), rem))
+    }
+}
+
+impl<I, O, P, F, G> Print<O> for Map<P, F, G, I> where P: Print<I>, G: Fn(&O) -> I {
+    fn print(&self, value: &O, output: &mut String) {
+        self.syntax.print(&(self.from)(value), output);
+    }
+}
+
+pub fn map<P, F, G, I>(syntax: P, into: F, from: G) -> Map<P, F, G, I> {
+    Map { syntax, into, from, inner: PhantomData }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Setting {
+        key: String,
+        values: Vec<String>,
+    }
+
+    fn setting() -> impl for<'a> Parser<'a, Setting> + Print<Setting> {
+        let name = || word(|c: char| c.is_ascii_alphanumeric());
+        map(
+            pair(terminated(name(), literal(" = ")), terminated(separated(name(), literal(", ")), literal(";"))),
+            |(key, values)| Setting { key, values },
+            |setting: &Setting| (setting.key.clone(), setting.values.clone()),
+        )
+    }
+
+    #[test]
+    fn print_writes_back_what_was_parsed() {
+        let input = "paths = a, b, c;";
+        let syntax = setting();
+
+        let (value, rem) = syntax.parse(input).expect("to parse a setting");
+        let actual = syntax.printed(&value);
+
+        assert_eq!((value.values.len(), rem), (3, ""));
+        assert_eq!(actual, input);
+    }
+
+    #[test]
+    fn print_writes_an_edited_value() {
+        let input = Setting { key: "empty".to_owned(), values: vec![] };
+        let syntax = setting();
+
+        let actual = syntax.printed(&input);
+
+        let expected = "empty = ;";
+        assert_eq!(actual, expected);
+        assert_eq!(syntax.parse(&actual), Ok((input, "")));
+    }
+}
@@ -0,0 +1,240 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+// Lines and columns count from zero, and columns are byte offsets into the
+// line, so that both directions are a binary search and a subtraction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+    length: usize,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(source.match_indices('\n').map(|(offset, _)| offset + 1));
+        Self { starts, length: source.len() }
+    }
+
+    pub fn lines(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.starts.get(line).copied()
+    }
+
+    pub fn position(&self, offset: usize) -> Option<LineColumn> {
+        if offset > self.length {
+            return None;
+        }
+        let line = match self.starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        Some(LineColumn::new(line, offset - self.starts[line]))
+    }
+
+    pub fn offset(&self, position: LineColumn) -> Option<usize> {
+        let start = self.line_start(position.line)?;
+        let end = self.line_start(position.line + 1).map_or(self.length, |next| next - 1);
+        match start.checked_add(position.column)? {
+            offset if offset <= end => Some(offset),
+            _ => None,
+        }
+    }
+
+    // Like `position`, but with the column measured as it is displayed rather
+    // than in bytes.
+    pub fn display_position(&self, source: &str, offset: usize, widths: &ColumnWidths) -> Option<LineColumn> {
+        let position = self.position(offset)?;
+        let line = &source[self.starts[position.line]..];
+        Some(LineColumn::new(position.line, widths.column(line, position.column)))
+    }
+}
+
+// How wide characters are on screen, so a caret under a line of source ends
+// up under the right character. Tabs move to the next tab stop. Wide
+// characters (CJK, Hangul, full-width forms and emoji) take two columns and
+// combining marks none, unless `wide_characters` is turned off, in which case
+// every character but a tab takes one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnWidths {
+    tab_width: usize,
+    wide_characters: bool,
+}
+
+impl Default for ColumnWidths {
+    fn default() -> Self {
+        Self { tab_width: 8, wide_characters: true }
+    }
+}
+
+impl ColumnWidths {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tab_width(mut self, width: usize) -> Self {
+        self.tab_width = width.max(1);
+        self
+    }
+
+    pub fn wide_characters(mut self, wide: bool) -> Self {
+        self.wide_characters = wide;
+        self
+    }
+
+    fn width(&self, c: char, column: usize) -> usize {
+        match c {
+            '\t' => self.tab_width - column % self.tab_width,
+            _ if !self.wide_characters => 1,
+            _ if is_zero_width(c) => 0,
+            _ if is_wide(c) => 2,
+            _ => 1,
+        }
+    }
+
+    // The display column of the byte offset `byte` within `line`.
+    pub fn column(&self, line: &str, byte: usize) -> usize {
+        line.char_indices()
+            .take_while(|(offset, c)| *offset < byte && *c != '\n')
+            .fold(0, |column, (_, c)| column + self.width(c, column))
+    }
+
+    // Spaces up to `start` and a caret under every column up to `end`, both
+    // byte offsets into `line`. An empty range still gets one caret.
+    pub fn underline(&self, line: &str, start: usize, end: usize) -> String {
+        let from = self.column(line, start);
+        let to = self.column(line, end.max(start));
+        format!("{}{}", " ".repeat(from), "^".repeat((to - from).max(1)))
+    }
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(c as u32, 0x0300..=0x036F | 0x200B..=0x200F | 0x20D0..=0x20FF | 0xFE00..=0xFE0F | 0xFE20..=0xFE2F)
+}
+
+fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x1F900..=0x1F9FF
+        | 0x20000..=0x3FFFD)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_map_to_lines_and_columns() {
+        let index = LineIndex::new("ab\ncde\n\nf");
+
+        let actual: Vec<_> = [0, 2, 3, 5, 7, 8, 9].iter().map(|offset| index.position(*offset)).collect();
+
+        let expected = vec![
+            Some(LineColumn::new(0, 0)),
+            Some(LineColumn::new(0, 2)),
+            Some(LineColumn::new(1, 0)),
+            Some(LineColumn::new(1, 2)),
+            Some(LineColumn::new(2, 0)),
+            Some(LineColumn::new(3, 0)),
+            Some(LineColumn::new(3, 1)),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn positions_map_back_to_offsets() {
+        let source = "ab\ncde\n\nf";
+        let index = LineIndex::new(source);
+
+        for offset in 0..=source.len() {
+            let position = index.position(offset).expect("offset to be in the source");
+            assert_eq!(index.offset(position), Some(offset));
+        }
+    }
+
+    #[test]
+    fn positions_outside_the_source_are_rejected() {
+        let index = LineIndex::new("ab\ncd");
+
+        assert_eq!(index.position(6), None);
+        assert_eq!(index.offset(LineColumn::new(0, 3)), None);
+        assert_eq!(index.offset(LineColumn::new(2, 0)), None);
+        assert_eq!(index.offset(LineColumn::new(0, usize::MAX)), None);
+        assert_eq!(index.offset(LineColumn::new(1, usize::MAX)), None);
+    }
+
+    #[test]
+    fn display_columns_follow_tab_stops_and_wide_characters() {
+        let source = "x = 1\n\tab\t日本 ✓\n";
+        let index = LineIndex::new(source);
+        let widths = ColumnWidths::new().tab_width(4);
+
+        let actual: Vec<_> = [6, 7, 9, 10, 13, 17].iter().map(|offset| index.display_position(source, *offset, &widths)).collect();
+
+        let expected = vec![
+            Some(LineColumn::new(1, 0)),
+            Some(LineColumn::new(1, 4)),
+            Some(LineColumn::new(1, 6)),
+            Some(LineColumn::new(1, 8)),
+            Some(LineColumn::new(1, 10)),
+            Some(LineColumn::new(1, 13)),
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn underline_spans_wide_characters() {
+        let input = "名前 = \"x\"";
+        let widths = ColumnWidths::new();
+
+        let actual = widths.underline(input, 0, 6);
+
+        let expected = "^^^^";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn underline_marks_an_empty_range_with_one_caret() {
+        let input = "名前 = \"x\"";
+        let widths = ColumnWidths::new();
+
+        let actual = widths.underline(input, 10, 10);
+
+        let expected = "        ^";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn underline_counts_every_character_once_without_wide_characters() {
+        let input = "名前 = \"x\"";
+        let widths = ColumnWidths::new().wide_characters(false);
+
+        let actual = widths.underline(input, 7, 12);
+
+        let expected = "   ^^^^^";
+        assert_eq!(actual, expected);
+    }
+}
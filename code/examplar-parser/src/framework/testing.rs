@@ -0,0 +1,217 @@
+use std::fmt::Debug;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use super::{Parser, ParseError, Found};
+use super::source::LineIndex;
+
+#[macro_export]
+macro_rules! assert_parses {
+    ($parser:expr, $input:expr, $expected:expr $(,)?) => {
+        $crate::framework::testing::check_parses(&$parser, $input, $expected, "")
+    };
+    ($parser:expr, $input:expr, $expected:expr, $rest:expr $(,)?) => {
+        $crate::framework::testing::check_parses(&$parser, $input, $expected, $rest)
+    };
+}
+
+#[macro_export]
+macro_rules! assert_fails_at {
+    ($parser:expr, $input:expr, $offset:expr $(,)?) => {
+        $crate::framework::testing::check_fails_at(&$parser, $input, $offset)
+    };
+}
+
+#[track_caller]
+pub fn check_parses<'a, T, P>(parser: &P, input: &'a str, expected: T, rest: &str) where T: Debug + PartialEq, P: Parser<'a, T> {
+    match parser.parse(input) {
+        Ok((value, rem)) if value == expected && rem == rest => {}
+
+        Ok((value, rem)) => {
+            let expected = format!("{:#?}\nrest: {:?}", expected, rest);
+            let actual = format!("{:#?}\nrest: {:?}", value, rem);
+            panic!("parse of {:?} did not match (- expected, + actual):\n{}", input, diff(&expected, &actual));
+        }
+
+        Err(e) => panic!("expected {:?} to parse, but it failed: {}", input, e),
+    }
+}
+
+// Errors do not record where they happened, so the check is that what the
+// error says it found is what the input holds at `offset`.
+#[track_caller]
+pub fn check_fails_at<'a, T, P>(parser: &P, input: &'a str, offset: usize) where T: Debug, P: Parser<'a, T> {
+    let error = match parser.parse(input) {
+        Ok((value, rem)) => panic!("expected {:?} to fail at {}, but it parsed {:#?} with rest {:?}", input, offset, value, rem),
+        Err(error) => error,
+    };
+    let at_offset = match (&error, error.found()) {
+        (ParseError::EndOfInput, _) | (_, Some(Found::EndOfInput)) => offset == input.len(),
+        (_, Some(Found::Character(c))) => input.get(offset..).is_some_and(|rest| rest.starts_with(*c)),
+        (_, Some(Found::Text(text))) => input.get(offset..).is_some_and(|rest| rest.starts_with(text.as_str())),
+        (_, None) => panic!("expected {:?} to fail at {}, but the error has no location: {}", input, offset, error),
+    };
+    if !at_offset {
+        panic!("expected the parse to fail here:\n{}", report(input, offset, &error.to_string()));
+    }
+}
+
+pub fn report(input: &str, offset: usize, message: &str) -> String {
+    let index = LineIndex::new(input);
+    let position = match index.position(offset) {
+        Some(position) => position,
+        None => return format!("{}\n --> offset {} is past the end of the input", message, offset),
+    };
+    let start = index.line_start(position.line).unwrap_or(0);
+    let line = input[start..].lines().next().unwrap_or("");
+    let column = input[start..offset].chars().count();
+    let number = (position.line + 1).to_string();
+    let gutter = " ".repeat(number.len());
+    format!(
+        "{}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}^",
+        message, gutter, position.line + 1, column + 1, gutter, number, line, gutter, " ".repeat(column),
+    )
+}
+
+// A line diff over the longest common subsequence, marking lines only in
+// `expected` with '-' and lines only in `actual` with '+'.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!("  {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    lines.join("\n")
+}
+
+// Shrinks an input that makes `fails` return true down to one where removing
+// any single character no longer does, by removing ever smaller chunks of it
+// (delta debugging). Candidates are tried in a fixed order, so the same input
+// always shrinks to the same reproducer.
+pub fn minimize<F>(input: &str, fails: F) -> String where F: Fn(&str) -> bool {
+    let mut chars: Vec<char> = input.chars().collect();
+    if !fails(input) {
+        return input.to_owned();
+    }
+    let mut chunks = 2;
+    while chars.len() >= 2 {
+        let size = chars.len().div_ceil(chunks);
+        let smaller = (0..chars.len()).step_by(size).find_map(|start| {
+            let mut candidate = chars.clone();
+            candidate.drain(start..(start + size).min(chars.len()));
+            let text: String = candidate.iter().collect();
+            if fails(&text) { Some(candidate) } else { None }
+        });
+        match smaller {
+            Some(candidate) => {
+                chars = candidate;
+                chunks = (chunks - 1).max(2);
+            }
+            None if chunks >= chars.len() => break,
+            None => chunks = (chunks * 2).min(chars.len()),
+        }
+    }
+    if chars.len() == 1 && fails("") {
+        chars.clear();
+    }
+    chars.into_iter().collect()
+}
+
+// For use with `minimize` when the misbehaviour is a panic. The panic message
+// is still printed for every candidate that panics.
+pub fn panics<F>(run: F) -> bool where F: FnOnce() {
+    catch_unwind(AssertUnwindSafe(run)).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framework::{ParseResult, character, digit, literal, number};
+
+    #[test]
+    fn assert_parses_accepts_a_matching_parse() {
+        crate::assert_parses!(number(), "12", 12);
+        crate::assert_parses!(number(), "12;", 12, ";");
+    }
+
+    #[test]
+    #[should_panic(expected = "- 13\n+ 12")]
+    fn assert_parses_shows_a_diff() {
+        crate::assert_parses!(number(), "12", 13);
+    }
+
+    #[test]
+    fn assert_fails_at_accepts_the_failing_offset() {
+        crate::assert_fails_at!(literal("ab"), "ax", 0);
+        crate::assert_fails_at!(character('a'), "", 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "1 | x1\n  |  ^")]
+    fn assert_fails_at_reports_where_it_expected_the_failure() {
+        crate::assert_fails_at!(digit(), "x1", 1);
+    }
+
+    #[test]
+    fn report_points_at_the_offset() {
+        let input = "first\nsecond line\n";
+
+        let actual = report(input, 13, "expected digit, found 'l'");
+
+        let expected = "expected digit, found 'l'\n --> 2:8\n  |\n2 | second line\n  |        ^";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn minimize_shrinks_to_what_still_fails() {
+        let input = "let x = (1 + (2 * 3)) + [4];";
+
+        let actual = minimize(input, |candidate| candidate.contains('[') && candidate.contains('*'));
+
+        let expected = "*[";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn minimize_finds_a_panicking_input() {
+        fn fragile(input: &str) -> ParseResult<'_, ()> {
+            assert!(!input.contains('X'), "cannot handle X");
+            Ok(((), input))
+        }
+        let input = "abcabcXabc";
+
+        let actual = minimize(input, |candidate| panics(|| drop(fragile.parse(candidate))));
+
+        let expected = "X";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn diff_marks_changed_lines() {
+        let actual = diff("a\nb\nc", "a\nx\nc\nd");
+
+        let expected = "  a\n- b\n+ x\n  c\n+ d";
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,213 @@
+#[cfg(feature = "unicode-normalization")]
+use unicode_normalization::UnicodeNormalization;
+#[cfg(feature = "unicode-segmentation")]
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{Parser, ParseError, ParseResult, Found};
+
+// Simple case folding: a character is upper-cased and then lower-cased, so
+// forms like final sigma ('ς') fold together with the rest ('σ'). Characters
+// that either step would turn into more than one (e.g. 'ß' or 'İ') are left
+// as they are, so matching never changes the number of characters compared.
+pub fn fold(c: char) -> char {
+    single(c.to_uppercase()).and_then(|upper| single(upper.to_lowercase())).unwrap_or(c)
+}
+
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+pub struct CaselessLiteral<'p>(&'p str);
+
+impl<'a, 'p> Parser<'a, &'a str> for CaselessLiteral<'p> {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        let mut rest = input.char_indices();
+        for expected in self.0.chars() {
+            match rest.next() {
+                Some((_, c)) if fold(c) == fold(expected) => {}
+                _ => return Err(ParseError::ExpectingLiteral(self.0.to_owned(), Found::snippet(input, self.0.chars().count()))),
+            }
+        }
+        let len = rest.next().map_or(input.len(), |(offset, _)| offset);
+        Ok((&input[..len], &input[len..]))
+    }
+}
+
+pub fn literal_caseless(match_ignoring_case: &str) -> CaselessLiteral<'_> {
+    CaselessLiteral(match_ignoring_case)
+}
+
+#[cfg(feature = "unicode-normalization")]
+pub struct NormalizedLiteral {
+    text: String,
+    target: Vec<char>,
+    caseless: bool,
+}
+
+#[cfg(feature = "unicode-normalization")]
+impl NormalizedLiteral {
+    pub fn new(text: &str, caseless: bool) -> Self {
+        let target = Self::normalize(text, caseless);
+        Self { text: text.to_owned(), target, caseless }
+    }
+
+    fn normalize(text: &str, caseless: bool) -> Vec<char> {
+        text.nfc().map(|c| if caseless { fold(c) } else { c }).collect()
+    }
+}
+
+// Input is matched by normalizing ever longer prefixes of it. A prefix is
+// only accepted if the next character would not combine with its last one.
+#[cfg(feature = "unicode-normalization")]
+impl<'a> Parser<'a, &'a str> for NormalizedLiteral {
+    fn parse(&self, input: &'a str) -> ParseResult<'a, &'a str> {
+        let ends = input.char_indices().map(|(offset, _)| offset).skip(1).chain(Some(input.len()));
+        for end in ends {
+            let candidate = Self::normalize(&input[..end], self.caseless);
+            if candidate.len() > self.target.len() {
+                break;
+            }
+            let combines = input[end..].chars().next().is_some_and(unicode_normalization::char::is_combining_mark);
+            if candidate == self.target && !combines {
+                return Ok((&input[..end], &input[end..]));
+            }
+        }
+        Err(ParseError::ExpectingLiteral(self.text.clone(), Found::snippet(input, self.text.chars().count())))
+    }
+}
+
+#[cfg(feature = "unicode-normalization")]
+pub fn literal_normalized<'a>(text: &str) -> impl Parser<'a, &'a str> {
+    NormalizedLiteral::new(text, false)
+}
+
+#[cfg(feature = "unicode-normalization")]
+pub fn literal_normalized_caseless<'a>(text: &str) -> impl Parser<'a, &'a str> {
+    NormalizedLiteral::new(text, true)
+}
+
+// A grapheme is what a reader sees as one character, such as an emoji with
+// modifiers or a letter followed by combining marks.
+#[cfg(feature = "unicode-segmentation")]
+pub fn any_grapheme<'a>() -> impl Parser<'a, &'a str> {
+    move |input: &'a str| {
+        match input.graphemes(true).next() {
+            Some(grapheme) => Ok((grapheme, &input[grapheme.len()..])),
+            None => Err(ParseError::EndOfInput),
+        }
+    }
+}
+
+#[cfg(feature = "unicode-segmentation")]
+pub fn take_graphemes<'a>(count: usize) -> impl Parser<'a, &'a str> {
+    move |input: &'a str| {
+        let mut graphemes = input.grapheme_indices(true);
+        if count > 0 && graphemes.nth(count - 1).is_none() {
+            return Err(ParseError::EndOfInput);
+        }
+        let len = graphemes.next().map_or(input.len(), |(offset, _)| offset);
+        Ok((&input[..len], &input[len..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_caseless_matches_any_case() {
+        let input = "STRAßE und";
+        let parser = literal_caseless("straße");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("STRAßE", " und"));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn literal_caseless_folds_final_sigma() {
+        let input = "ΟΔΟΣ";
+        let parser = literal_caseless("οδος");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("ΟΔΟΣ", ""));
+        assert_eq!(actual, expected);
+        assert_eq!(literal_caseless("σ").parse("ς"), Ok(("ς", "")));
+    }
+
+    #[test]
+    fn literal_caseless_reports_what_it_found() {
+        let input = "Strand";
+        let parser = literal_caseless("straße");
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingLiteral("straße".to_owned(), Found::Text("Strand".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn literal_normalized_matches_decomposed_input() {
+        let input = "cafe\u{301}!";
+        let parser = literal_normalized("café");
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("cafe\u{301}", "!"));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn literal_normalized_does_not_split_combining_sequences() {
+        let input = "cafe\u{301}";
+        let parser = literal_normalized("cafe");
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::ExpectingLiteral("cafe".to_owned(), Found::Text("cafe".to_owned())));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn any_grapheme_keeps_emoji_sequences_together() {
+        let input = "\u{1F44B}\u{1F3FD}!";
+        let parser = any_grapheme();
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("\u{1F44B}\u{1F3FD}", "!"));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn take_graphemes_counts_combined_characters_once() {
+        let input = "e\u{301}ab";
+        let parser = take_graphemes(2);
+
+        let actual = parser.parse(input);
+
+        let expected = Ok(("e\u{301}a", "b"));
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn take_graphemes_fails_on_short_input() {
+        let input = "ab";
+        let parser = take_graphemes(3);
+
+        let actual = parser.parse(input);
+
+        let expected = Err(ParseError::EndOfInput);
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,9 +1,11 @@
 extern crate api;
 
-mod framework;
+pub mod framework;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 use api::{Rule, RenderConfig, LSystemRules, LSystem};
-use self::framework::{Parser, ParseError, literal, character, newline, number, at_least, many, any, blank_lines, end};
+use self::framework::{Parser, ParseError, literal, character, newline, number, at_least, many, satisfy_named, blank_lines, end};
 
 pub fn parse(input: &str) -> Result<LSystem<char>, ParseError> {
     let parser = system();
@@ -68,7 +70,7 @@ pub fn rules<'a>() -> impl Parser<'a, (Vec<char>, LSystemRules<char>)>  {
 }
 
 pub fn symbol<'a>() -> impl Parser<'a, char> {
-    any(is_symbol)
+    satisfy_named("symbol", is_symbol)
 }
 
 fn is_symbol(character: char) -> bool {
@@ -0,0 +1,44 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::framework::{Parser, ParseError, end};
+use crate::framework::grammar::{Grammar, Tree};
+
+// Parses the whole of `input` with `rule` from an EBNF grammar. A parse tree
+// comes back as nested `{ rule, children }` objects with tokens as strings;
+// errors as `{ stage, code, message }`, where the stage is "grammar" or
+// "input" and the code is the one `ParseError::code` gives.
+#[wasm_bindgen(js_name = parseWithGrammar)]
+pub fn parse_with_grammar(grammar: &str, rule: &str, input: &str) -> Result<JsValue, JsValue> {
+    let grammar = Grammar::new(grammar).map_err(|e| error("grammar", &e))?;
+    let parser = grammar.rule(rule).map_err(|e| error("grammar", &e))?;
+    let (tree, _) = end(parser).parse(input).map_err(|e| error("input", &e))?;
+    Ok(tree_to_js(&tree))
+}
+
+fn tree_to_js(tree: &Tree) -> JsValue {
+    match tree {
+        Tree::Node(name, children) => {
+            let children: Array = children.iter().map(tree_to_js).collect();
+            object(&[("rule", JsValue::from_str(name)), ("children", children.into())])
+        }
+        Tree::Token(text) => JsValue::from_str(text),
+        Tree::Trivia(text) => object(&[("trivia", JsValue::from_str(text))]),
+    }
+}
+
+fn error(stage: &str, error: &ParseError) -> JsValue {
+    object(&[
+        ("stage", JsValue::from_str(stage)),
+        ("code", JsValue::from_str(error.code())),
+        ("message", JsValue::from_str(&error.to_string())),
+    ])
+}
+
+fn object(entries: &[(&str, JsValue)]) -> JsValue {
+    let object = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&object, &JsValue::from_str(key), value).expect("a plain object to accept new properties");
+    }
+    object.into()
+}